@@ -312,6 +312,19 @@ fn setup_build() -> (
     // Enable TRNG
     defines.push(("CFG_USE_CHACHA20_RAND", None));
 
+    #[cfg(all(feature = "max_connections_2", feature = "max_connections_3"))]
+    compile_error!("Only one max connections feature flag can be set!");
+
+    #[cfg(feature = "max_connections_2")]
+    {
+        defines.push(("CFG_MAX_CONNECTIONS", Some("2")));
+    }
+
+    #[cfg(feature = "max_connections_3")]
+    {
+        defines.push(("CFG_MAX_CONNECTIONS", Some("3")));
+    }
+
     #[cfg(feature = "address_mode_public")]
     {
         defines.push(("USER_CFG_ADDRESS_MODE", Some("APP_CFG_ADDR_PUB")));
@@ -42,8 +42,11 @@
 /* to service multiple connections. It is used for GAP central role applications. For GAP peripheral role it    */
 /* should be set to 1 for optimizing memory utilization.                                                        */
 /*      - MAX value for DA14531: 3                                                                              */
+/*      - Overridden by the max_connections_2 and max_connections_3 cargo features                              */
 /****************************************************************************************************************/
+#ifndef CFG_MAX_CONNECTIONS
 #define CFG_MAX_CONNECTIONS (1)
+#endif
 
 /****************************************************************************************************************/
 /* Enables development/debug mode. For production mode builds it must be disabled.                              */
@@ -0,0 +1,433 @@
+//! Zero-copy parser for AD structures of advertising reports and scan responses.
+
+use super::ad_types::{
+    ADV_TYPE_APPEARANCE, ADV_TYPE_COMPLETE_LIST_128BIT_SERVICE_IDS,
+    ADV_TYPE_COMPLETE_LIST_16BIT_SERVICE_IDS, ADV_TYPE_COMPLETE_LIST_32BIT_SERVICE_IDS,
+    ADV_TYPE_COMPLETE_LOCAL_NAME, ADV_TYPE_FLAGS, ADV_TYPE_INCOMPLETE_LIST_128BIT_SERVICE_IDS,
+    ADV_TYPE_INCOMPLETE_LIST_16BIT_SERVICE_IDS, ADV_TYPE_INCOMPLETE_LIST_32BIT_SERVICE_IDS,
+    ADV_TYPE_MANUFACTURER_SPECIFIC_DATA, ADV_TYPE_SERVICE_DATA_128BIT_UUID,
+    ADV_TYPE_SERVICE_DATA_16BIT_UUID, ADV_TYPE_SERVICE_DATA_32BIT_UUID,
+    ADV_TYPE_SHORTENED_LOCAL_NAME, ADV_TYPE_TX_POWER_LEVEL,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdParseError {
+    /// The length of an AD structure exceeds the remaining data
+    Truncated,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdStructure<'a> {
+    pub ad_type: u8,
+    pub data: &'a [u8],
+}
+
+impl<'a> AdStructure<'a> {
+    /// `data` if the structure is of one of `ad_types` and has a non-zero length which is a
+    /// multiple of `item_len`
+    fn data_of(&self, ad_types: &[u8], item_len: usize) -> Option<&'a [u8]> {
+        if ad_types.contains(&self.ad_type)
+            && !self.data.is_empty()
+            && self.data.len() % item_len == 0
+        {
+            Some(self.data)
+        } else {
+            None
+        }
+    }
+
+    /// Data of a service data structure, which starts with a UUID of `uuid_len` bytes
+    fn split_uuid(&self, ad_type: u8, uuid_len: usize) -> Option<(&'a [u8], &'a [u8])> {
+        if self.ad_type == ad_type && self.data.len() >= uuid_len {
+            Some(self.data.split_at(uuid_len))
+        } else {
+            None
+        }
+    }
+
+    pub fn flags(&self) -> Option<u8> {
+        match self.data_of(&[ADV_TYPE_FLAGS], 1)? {
+            [flags] => Some(*flags),
+            _ => None,
+        }
+    }
+
+    /// Complete or shortened local name
+    pub fn name(&self) -> Option<&'a str> {
+        let name = self.data_of(
+            &[ADV_TYPE_COMPLETE_LOCAL_NAME, ADV_TYPE_SHORTENED_LOCAL_NAME],
+            1,
+        )?;
+
+        core::str::from_utf8(name).ok()
+    }
+
+    pub fn is_complete_name(&self) -> bool {
+        self.ad_type == ADV_TYPE_COMPLETE_LOCAL_NAME
+    }
+
+    /// Complete or incomplete list of 16-bit service UUIDs
+    pub fn uuid16_list(&self) -> Option<impl Iterator<Item = u16> + 'a> {
+        let data = self.data_of(
+            &[
+                ADV_TYPE_COMPLETE_LIST_16BIT_SERVICE_IDS,
+                ADV_TYPE_INCOMPLETE_LIST_16BIT_SERVICE_IDS,
+            ],
+            2,
+        )?;
+
+        Some(
+            data.chunks_exact(2)
+                .map(|uuid| u16::from_le_bytes([uuid[0], uuid[1]])),
+        )
+    }
+
+    /// Complete or incomplete list of 32-bit service UUIDs
+    pub fn uuid32_list(&self) -> Option<impl Iterator<Item = u32> + 'a> {
+        let data = self.data_of(
+            &[
+                ADV_TYPE_COMPLETE_LIST_32BIT_SERVICE_IDS,
+                ADV_TYPE_INCOMPLETE_LIST_32BIT_SERVICE_IDS,
+            ],
+            4,
+        )?;
+
+        Some(
+            data.chunks_exact(4)
+                .map(|uuid| u32::from_le_bytes([uuid[0], uuid[1], uuid[2], uuid[3]])),
+        )
+    }
+
+    /// Complete or incomplete list of 128-bit service UUIDs in little endian byte order
+    pub fn uuid128_list(&self) -> Option<impl Iterator<Item = &'a [u8; 16]> + 'a> {
+        let data = self.data_of(
+            &[
+                ADV_TYPE_COMPLETE_LIST_128BIT_SERVICE_IDS,
+                ADV_TYPE_INCOMPLETE_LIST_128BIT_SERVICE_IDS,
+            ],
+            16,
+        )?;
+
+        Some(data.chunks_exact(16).map(|uuid| uuid.try_into().unwrap()))
+    }
+
+    pub fn service_data16(&self) -> Option<(u16, &'a [u8])> {
+        let (uuid, data) = self.split_uuid(ADV_TYPE_SERVICE_DATA_16BIT_UUID, 2)?;
+
+        Some((u16::from_le_bytes([uuid[0], uuid[1]]), data))
+    }
+
+    pub fn service_data32(&self) -> Option<(u32, &'a [u8])> {
+        let (uuid, data) = self.split_uuid(ADV_TYPE_SERVICE_DATA_32BIT_UUID, 4)?;
+
+        Some((
+            u32::from_le_bytes([uuid[0], uuid[1], uuid[2], uuid[3]]),
+            data,
+        ))
+    }
+
+    /// The UUID is returned in little endian byte order
+    pub fn service_data128(&self) -> Option<(&'a [u8; 16], &'a [u8])> {
+        let (uuid, data) = self.split_uuid(ADV_TYPE_SERVICE_DATA_128BIT_UUID, 16)?;
+
+        Some((uuid.try_into().unwrap(), data))
+    }
+
+    /// Company identifier and data
+    pub fn manufacturer_data(&self) -> Option<(u16, &'a [u8])> {
+        let (company_id, data) = self.split_uuid(ADV_TYPE_MANUFACTURER_SPECIFIC_DATA, 2)?;
+
+        Some((u16::from_le_bytes([company_id[0], company_id[1]]), data))
+    }
+
+    /// TX power level in dBm
+    pub fn tx_power(&self) -> Option<i8> {
+        match self.data_of(&[ADV_TYPE_TX_POWER_LEVEL], 1)? {
+            [tx_power] => Some(*tx_power as i8),
+            _ => None,
+        }
+    }
+
+    pub fn appearance(&self) -> Option<u16> {
+        match self.data_of(&[ADV_TYPE_APPEARANCE], 2)? {
+            [low, high] => Some(u16::from_le_bytes([*low, *high])),
+            _ => None,
+        }
+    }
+}
+
+/// Iterator over the AD structures of advertising or scan response data.
+///
+/// A zero length field terminates the data. A truncated structure yields an error and ends the
+/// iteration.
+#[derive(Debug, Clone)]
+pub struct AdParser<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> AdParser<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// First well-formed AD structure of type `ad_type`
+    pub fn find_type(&self, ad_type: u8) -> Option<AdStructure<'a>> {
+        self.clone()
+            .map_while(Result::ok)
+            .find(|ad| ad.ad_type == ad_type)
+    }
+}
+
+impl<'a> Iterator for AdParser<'a> {
+    type Item = Result<AdStructure<'a>, AdParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (&len, rest) = self.data.split_first()?;
+        let len = len as usize;
+
+        if len == 0 {
+            self.data = &[];
+            return None;
+        }
+
+        if len > rest.len() {
+            self.data = &[];
+            return Some(Err(AdParseError::Truncated));
+        }
+
+        let (structure, rest) = rest.split_at(len);
+        self.data = rest;
+
+        Some(Ok(AdStructure {
+            ad_type: structure[0],
+            data: &structure[1..],
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    fn single(data: &[u8]) -> AdStructure<'_> {
+        let mut parser = AdParser::new(data);
+        let ad = parser.next().unwrap().unwrap();
+
+        assert_eq!(parser.next(), None);
+
+        ad
+    }
+
+    #[test]
+    fn iterates_structures() {
+        let data = [0x02, 0x01, 0x06, 0x03, 0x03, 0xaa, 0xfe];
+        let ads: Vec<_> = AdParser::new(&data).collect();
+
+        assert_eq!(
+            ads,
+            [
+                Ok(AdStructure {
+                    ad_type: ADV_TYPE_FLAGS,
+                    data: &[0x06],
+                }),
+                Ok(AdStructure {
+                    ad_type: ADV_TYPE_COMPLETE_LIST_16BIT_SERVICE_IDS,
+                    data: &[0xaa, 0xfe],
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_data() {
+        assert_eq!(AdParser::new(&[]).next(), None);
+    }
+
+    #[test]
+    fn truncated_structure() {
+        let data = [0x02, 0x01, 0x06, 0x05, 0x09, b'a', b'b'];
+        let mut parser = AdParser::new(&data);
+
+        assert!(parser.next().unwrap().is_ok());
+        assert_eq!(parser.next(), Some(Err(AdParseError::Truncated)));
+        assert_eq!(parser.next(), None);
+    }
+
+    #[test]
+    fn truncated_length_only() {
+        let mut parser = AdParser::new(&[0x03]);
+
+        assert_eq!(parser.next(), Some(Err(AdParseError::Truncated)));
+        assert_eq!(parser.next(), None);
+    }
+
+    #[test]
+    fn zero_length_terminates() {
+        let data = [0x02, 0x01, 0x06, 0x00, 0x02, 0x0a, 0x00];
+        let mut parser = AdParser::new(&data);
+
+        assert!(parser.next().unwrap().is_ok());
+        assert_eq!(parser.next(), None);
+        assert_eq!(parser.next(), None);
+    }
+
+    #[test]
+    fn zero_padding_is_ignored() {
+        let mut data = [0; 31];
+        data[..3].copy_from_slice(&[0x02, 0x0a, 0xf8]);
+
+        assert_eq!(AdParser::new(&data).count(), 1);
+    }
+
+    #[test]
+    fn type_without_data() {
+        let ad = single(&[0x01, ADV_TYPE_COMPLETE_LOCAL_NAME]);
+
+        assert_eq!(ad.data, &[]);
+        assert_eq!(ad.name(), None);
+    }
+
+    #[test]
+    fn find_type() {
+        let data = [0x02, 0x01, 0x06, 0x02, 0x0a, 0x04];
+        let parser = AdParser::new(&data);
+
+        assert_eq!(
+            parser
+                .find_type(ADV_TYPE_TX_POWER_LEVEL)
+                .unwrap()
+                .tx_power(),
+            Some(4)
+        );
+        assert_eq!(parser.find_type(ADV_TYPE_APPEARANCE), None);
+    }
+
+    #[test]
+    fn find_type_stops_at_truncated_structure() {
+        let data = [0x05, 0x01, 0x06, 0x02, 0x0a, 0x04];
+
+        assert_eq!(
+            AdParser::new(&data).find_type(ADV_TYPE_TX_POWER_LEVEL),
+            None
+        );
+    }
+
+    #[test]
+    fn flags() {
+        assert_eq!(single(&[0x02, 0x01, 0x06]).flags(), Some(0x06));
+        assert_eq!(single(&[0x03, 0x01, 0x06, 0x00]).flags(), None);
+        assert_eq!(single(&[0x02, 0x0a, 0x06]).flags(), None);
+    }
+
+    #[test]
+    fn names() {
+        let complete = single(&[0x04, 0x09, b'D', b'A', b'1']);
+        let shortened = single(&[0x03, 0x08, b'D', b'A']);
+
+        assert_eq!(complete.name(), Some("DA1"));
+        assert!(complete.is_complete_name());
+        assert_eq!(shortened.name(), Some("DA"));
+        assert!(!shortened.is_complete_name());
+    }
+
+    #[test]
+    fn invalid_utf8_name() {
+        assert_eq!(single(&[0x03, 0x09, 0xc3, 0x28]).name(), None);
+    }
+
+    #[test]
+    fn uuid16_list() {
+        let ad = single(&[0x05, 0x02, 0x0d, 0x18, 0x0f, 0x18]);
+
+        assert!(ad.uuid16_list().unwrap().eq([0x180d, 0x180f]));
+        assert!(single(&[0x04, 0x03, 0x0d, 0x18, 0x0f])
+            .uuid16_list()
+            .is_none());
+    }
+
+    #[test]
+    fn uuid32_list() {
+        let ad = single(&[0x05, 0x05, 0x78, 0x56, 0x34, 0x12]);
+
+        assert!(ad.uuid32_list().unwrap().eq([0x1234_5678]));
+        assert!(single(&[0x03, 0x04, 0x78, 0x56]).uuid32_list().is_none());
+    }
+
+    #[test]
+    fn uuid128_list() {
+        let mut data = [0; 18];
+        data[0] = 17;
+        data[1] = ADV_TYPE_INCOMPLETE_LIST_128BIT_SERVICE_IDS;
+        let uuid = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f,
+        ];
+        data[2..].copy_from_slice(&uuid);
+
+        let ad = single(&data);
+        let uuids: Vec<_> = ad.uuid128_list().unwrap().collect();
+
+        assert_eq!(uuids, [&uuid]);
+
+        let mut short = data;
+        short[0] = 16;
+
+        assert!(single(&short[..17]).uuid128_list().is_none());
+    }
+
+    #[test]
+    fn service_data() {
+        let ad16 = single(&[0x05, 0x16, 0xaa, 0xfe, 0x10, 0x20]);
+        let ad32 = single(&[0x06, 0x20, 0x78, 0x56, 0x34, 0x12, 0x01]);
+
+        assert_eq!(ad16.service_data16(), Some((0xfeaa, &[0x10, 0x20][..])));
+        assert_eq!(ad16.service_data32(), None);
+        assert_eq!(ad32.service_data32(), Some((0x1234_5678, &[0x01][..])));
+        assert_eq!(single(&[0x02, 0x16, 0xaa]).service_data16(), None);
+    }
+
+    #[test]
+    fn service_data128() {
+        let mut data = [0; 19];
+        data[0] = 18;
+        data[1] = ADV_TYPE_SERVICE_DATA_128BIT_UUID;
+        data[2..18].copy_from_slice(&[0x11; 16]);
+        data[18] = 0x42;
+
+        let (uuid, service_data) = single(&data).service_data128().unwrap();
+
+        assert_eq!(uuid, &[0x11; 16]);
+        assert_eq!(service_data, &[0x42]);
+
+        let mut short = data;
+        short[0] = 16;
+
+        assert_eq!(single(&short[..17]).service_data128(), None);
+    }
+
+    #[test]
+    fn manufacturer_data() {
+        let ad = single(&[0x05, 0xff, 0x4c, 0x00, 0x02, 0x15]);
+
+        assert_eq!(ad.manufacturer_data(), Some((0x004c, &[0x02, 0x15][..])));
+        assert_eq!(
+            single(&[0x03, 0xff, 0x4c, 0x00]).manufacturer_data(),
+            Some((0x004c, &[][..]))
+        );
+        assert_eq!(single(&[0x02, 0xff, 0x4c]).manufacturer_data(), None);
+    }
+
+    #[test]
+    fn tx_power() {
+        assert_eq!(single(&[0x02, 0x0a, 0xf8]).tx_power(), Some(-8));
+        assert_eq!(single(&[0x03, 0x0a, 0xf8, 0x00]).tx_power(), None);
+    }
+
+    #[test]
+    fn appearance() {
+        assert_eq!(single(&[0x03, 0x19, 0x40, 0x02]).appearance(), Some(0x0240));
+        assert_eq!(single(&[0x02, 0x19, 0x40]).appearance(), None);
+    }
+}
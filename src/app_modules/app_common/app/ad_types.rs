@@ -0,0 +1,106 @@
+//! AD types of advertising and scan response data.
+
+/// Flags
+pub const ADV_TYPE_FLAGS: u8 = 0x01;
+
+/// Incomplete list of 16-bit Service IDs
+pub const ADV_TYPE_INCOMPLETE_LIST_16BIT_SERVICE_IDS: u8 = 0x02;
+
+/// Complete list of 16-bit Service IDs
+pub const ADV_TYPE_COMPLETE_LIST_16BIT_SERVICE_IDS: u8 = 0x03;
+
+/// Incomplete list of 32-bit Service IDs (not relevant for Bluetooth 4.0)
+pub const ADV_TYPE_INCOMPLETE_LIST_32BIT_SERVICE_IDS: u8 = 0x04;
+
+/// Complete list of 32-bit Service IDs (not relevant for Bluetooth 4.0)
+pub const ADV_TYPE_COMPLETE_LIST_32BIT_SERVICE_IDS: u8 = 0x05;
+
+/// Incomplete list of 128-bit Service IDs
+pub const ADV_TYPE_INCOMPLETE_LIST_128BIT_SERVICE_IDS: u8 = 0x06;
+
+/// Complete list of 128-bit Service IDs
+pub const ADV_TYPE_COMPLETE_LIST_128BIT_SERVICE_IDS: u8 = 0x07;
+
+/// Shortened Local Name
+pub const ADV_TYPE_SHORTENED_LOCAL_NAME: u8 = 0x08;
+
+/// Complete Local Name
+pub const ADV_TYPE_COMPLETE_LOCAL_NAME: u8 = 0x09;
+
+/// TX Power Level (in dBm)
+pub const ADV_TYPE_TX_POWER_LEVEL: u8 = 0x0A;
+
+/// Class of Device
+pub const ADV_TYPE_CLASS_OF_DEVICE: u8 = 0x0D;
+
+/// Simple Pairing Hash C
+pub const ADV_TYPE_SIMPLE_PAIRING_HASH_C: u8 = 0x0E;
+
+/// Simple Pairing Randomizer R
+pub const ADV_TYPE_SIMPLE_PAIRING_RANDOM_R: u8 = 0x0F;
+
+/// Device ID
+pub const ADV_TYPE_DEVICE_ID: u8 = 0x10;
+
+/// Security Manager Out of Band Flags
+pub const ADV_TYPE_SECURITY_MAN_OUT_OF_BAND_FLAGS: u8 = 0x11;
+
+/// Slave Connection Interval Range
+pub const ADV_TYPE_SLAVE_CONNECTION_INTERVAL_RANGE: u8 = 0x12;
+
+/// List of 16-bit Service Solicitation UUIDs
+pub const ADV_TYPE_LIST_16BIT_SOLICITATION_UUIDS: u8 = 0x14;
+
+/// List of 32-bit Service Solicitation UUIDs
+pub const ADV_TYPE_LIST_32BIT_SOLICITATION_UUIDS: u8 = 0x1F;
+
+/// List of 128-bit Service Solicitation UUIDs
+pub const ADV_TYPE_LIST_128BIT_SOLICITATION_UUIDS: u8 = 0x15;
+
+/// Service Data - 16-bit UUID
+pub const ADV_TYPE_SERVICE_DATA_16BIT_UUID: u8 = 0x16;
+
+/// Service Data - 32-bit UUID
+pub const ADV_TYPE_SERVICE_DATA_32BIT_UUID: u8 = 0x20;
+
+/// Service Data - 128-bit UUID
+pub const ADV_TYPE_SERVICE_DATA_128BIT_UUID: u8 = 0x21;
+
+/// LE Secure Connections Confirmation Value
+pub const ADV_TYPE_LE_SECURE_CON_CONFIRM_VAL: u8 = 0x22;
+
+/// LE Secure Connections Random Value
+pub const ADV_TYPE_LE_SECURE_CON_RANDOM_VAL: u8 = 0x23;
+
+/// Public Target Address
+pub const ADV_TYPE_PUBLIC_TARGET_ADDRESS: u8 = 0x17;
+
+/// Random Target Address
+pub const ADV_TYPE_RANDOM_TARGET_ADDRESS: u8 = 0x18;
+
+/// Appearance
+pub const ADV_TYPE_APPEARANCE: u8 = 0x19;
+
+/// Advertising Interval
+pub const ADV_TYPE_ADVERTISING_INTERVAL: u8 = 0x1A;
+
+/// LE Bluetooth Device Address
+pub const ADV_TYPE_LE_BLUETOOTH_DEVICE_ADDRESS: u8 = 0x1B;
+
+/// LE Role
+pub const ADV_TYPE_LE_ROLE: u8 = 0x1C;
+
+/// Simple Pairing Hash C-256
+pub const ADV_TYPE_SIMPLE_PAIRING_HASH_C256: u8 = 0x1D;
+
+/// Simple Pairing Randomizer R-256
+pub const ADV_TYPE_SIMPLE_PAIRING_RANDOM_C256: u8 = 0x1E;
+
+/// Uniform Resource Identifier
+pub const ADV_TYPE_URI: u8 = 0x24;
+
+/// 3D Information Data
+pub const ADV_TYPE_INFO_DATA_3D: u8 = 0x3D;
+
+/// Manufacturer Specific Data
+pub const ADV_TYPE_MANUFACTURER_SPECIFIC_DATA: u8 = 0xFF;
@@ -1,180 +1,207 @@
-/// Incomplete list of 16-bit Service IDs
-#[allow(dead_code)]
-pub const ADV_TYPE_INCOMPLETE_LIST_16BIT_SERVICE_IDS: u8 = 0x02;
+use crate::platform::core_modules::common::{ADV_DATA_LEN, SCAN_RSP_DATA_LEN};
 
-/// Complete list of 16-bit Service IDs
-#[allow(dead_code)]
-pub const ADV_TYPE_COMPLETE_LIST_16BIT_SERVICE_IDS: u8 = 0x03;
+use super::ad_parser::AdParser;
 
-/// Incomplete list of 32-bit Service IDs (not relevant for Bluetooth 4.0)
-#[allow(dead_code)]
-pub const ADV_TYPE_INCOMPLETE_LIST_32BIT_SERVICE_IDS: u8 = 0x04;
+pub use super::ad_types::*;
 
-/// Complete list of 32-bit Service IDs (not relevant for Bluetooth 4.0)
-#[allow(dead_code)]
-pub const ADV_TYPE_COMPLETE_LIST_32BIT_SERVICE_IDS: u8 = 0x05;
-
-/// Incomplete list of 128-bit Service IDs
-#[allow(dead_code)]
-pub const ADV_TYPE_INCOMPLETE_LIST_128BIT_SERVICE_IDS: u8 = 0x06;
-
-/// Complete list of 128-bit Service IDs
-#[allow(dead_code)]
-pub const ADV_TYPE_COMPLETE_LIST_128BIT_SERVICE_IDS: u8 = 0x07;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdvDataError {
+    /// The AD structure does not fit into the remaining space
+    Overflow,
+}
 
-/// Shortened Local Name
-#[allow(dead_code)]
-pub const ADV_TYPE_SHORTENED_LOCAL_NAME: u8 = 0x08;
+pub type AdvDataResult<T = ()> = Result<T, AdvDataError>;
+
+/// Advertising or scan response data consisting of AD structures.
+///
+/// ```ignore
+/// let adv_data = AdvData::new()
+///     .add_uuid16_list(true, &[0xfd6b])?
+///     .add_manufacturer_data(0x0598, &[0x01, 0x90, 0x01, 0x02, 0x22])?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct AdvData {
+    data: [u8; ADV_DATA_LEN as usize],
+    len: u8,
+    max_len: u8,
+}
 
-/// Complete Local Name
-#[allow(dead_code)]
-pub const ADV_TYPE_COMPLETE_LOCAL_NAME: u8 = 0x09;
+impl AdvData {
+    /// Empty advertising data limited to `ADV_DATA_LEN` bytes.
+    pub const fn new() -> Self {
+        Self::with_max_len(ADV_DATA_LEN as u8)
+    }
 
-/// TX Power Level (in dBm)
-#[allow(dead_code)]
-pub const ADV_TYPE_TX_POWER_LEVEL: u8 = 0x0A;
+    /// Empty scan response data limited to `SCAN_RSP_DATA_LEN` bytes.
+    pub const fn new_scan_response() -> Self {
+        Self::with_max_len(SCAN_RSP_DATA_LEN as u8)
+    }
 
-/// Class of Device
-#[allow(dead_code)]
-pub const ADV_TYPE_CLASS_OF_DEVICE: u8 = 0x0D;
-
-/// Simple Pairing Hash C
-#[allow(dead_code)]
-pub const ADV_TYPE_SIMPLE_PAIRING_HASH_C: u8 = 0x0E;
+    const fn with_max_len(max_len: u8) -> Self {
+        Self {
+            data: [0; ADV_DATA_LEN as usize],
+            len: 0,
+            max_len,
+        }
+    }
 
-/// Simple Pairing Randomizer R
-#[allow(dead_code)]
-pub const ADV_TYPE_SIMPLE_PAIRING_RANDOM_R: u8 = 0x0F;
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data[..self.len as usize]
+    }
 
-/// Device ID
-#[allow(dead_code)]
-pub const ADV_TYPE_DEVICE_ID: u8 = 0x10;
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
 
-/// Security Manager Out of Band Flags
-#[allow(dead_code)]
-pub const ADV_TYPE_SECURITY_MAN_OUT_OF_BAND_FLAGS: u8 = 0x11;
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
 
-/// Slave Connection Interval Range
-#[allow(dead_code)]
-pub const ADV_TYPE_SLAVE_CONNECTION_INTERVAL_RANGE: u8 = 0x12;
+    pub fn iter(&self) -> AdParser<'_> {
+        AdParser::new(self.as_bytes())
+    }
 
-/// List of 16-bit Service Solicitation UUIDs
-#[allow(dead_code)]
-pub const ADV_TYPE_LIST_16BIT_SOLICITATION_UUIDS: u8 = 0x14;
+    /// Number of bytes left, including the length and type of a further AD structure
+    pub fn remaining(&self) -> usize {
+        (self.max_len - self.len) as usize
+    }
 
-/// List of 32-bit Service Solicitation UUIDs
-#[allow(dead_code)]
-pub const ADV_TYPE_LIST_32BIT_SOLICITATION_UUIDS: u8 = 0x1F;
+    /// Appends an AD structure built from `parts`.
+    fn add_parts(mut self, ad_type: u8, parts: &[&[u8]]) -> AdvDataResult<Self> {
+        let data_len: usize = parts.iter().map(|part| part.len()).sum();
 
-/// List of 128-bit Service Solicitation UUIDs
-#[allow(dead_code)]
-pub const ADV_TYPE_LIST_128BIT_SOLICITATION_UUIDS: u8 = 0x15;
+        if 2 + data_len > self.remaining() {
+            return Err(AdvDataError::Overflow);
+        }
 
-/// Service Data - 16-bit UUID
-#[allow(dead_code)]
-pub const ADV_TYPE_SERVICE_DATA_16BIT_UUID: u8 = 0x16;
+        let mut offset = self.len as usize;
 
-/// Service Data - 32-bit UUID
-#[allow(dead_code)]
-pub const ADV_TYPE_SERVICE_DATA_32BIT_UUID: u8 = 0x20;
+        self.data[offset] = 1 + data_len as u8;
+        self.data[offset + 1] = ad_type;
+        offset += 2;
 
-/// Service Data - 128-bit UUID
-#[allow(dead_code)]
-pub const ADV_TYPE_SERVICE_DATA_128BIT_UUID: u8 = 0x21;
+        for part in parts {
+            self.data[offset..offset + part.len()].copy_from_slice(part);
+            offset += part.len();
+        }
 
-/// LE Secure Connections Confirmation Value
-#[allow(dead_code)]
-pub const ADV_TYPE_LE_SECURE_CON_CONFIRM_VAL: u8 = 0x22;
+        self.len = offset as u8;
 
-/// LE Secure Connections Random Value
-#[allow(dead_code)]
-pub const ADV_TYPE_LE_SECURE_CON_RANDOM_VAL: u8 = 0x23;
+        Ok(self)
+    }
 
-/// Public Target Address
-#[allow(dead_code)]
-pub const ADV_TYPE_PUBLIC_TARGET_ADDRESS: u8 = 0x17;
+    pub fn add_raw(self, ad_type: u8, data: &[u8]) -> AdvDataResult<Self> {
+        self.add_parts(ad_type, &[data])
+    }
 
-/// Random Target Address
-#[allow(dead_code)]
-pub const ADV_TYPE_RANDOM_TARGET_ADDRESS: u8 = 0x18;
+    /// The stack adds the flags by itself when advertising in a discoverable mode
+    pub fn add_flags(self, flags: u8) -> AdvDataResult<Self> {
+        self.add_raw(ADV_TYPE_FLAGS, &[flags])
+    }
 
-/// Appearance
-#[allow(dead_code)]
-pub const ADV_TYPE_APPEARANCE: u8 = 0x19;
+    pub fn add_uuid16_list(self, complete: bool, uuids: &[u16]) -> AdvDataResult<Self> {
+        let ad_type = if complete {
+            ADV_TYPE_COMPLETE_LIST_16BIT_SERVICE_IDS
+        } else {
+            ADV_TYPE_INCOMPLETE_LIST_16BIT_SERVICE_IDS
+        };
+
+        let mut data = [0u8; ADV_DATA_LEN as usize];
+        let data_len = uuids.len() * 2;
+        if data_len > data.len() {
+            return Err(AdvDataError::Overflow);
+        }
+        for (chunk, uuid) in data.chunks_exact_mut(2).zip(uuids) {
+            chunk.copy_from_slice(&uuid.to_le_bytes());
+        }
+
+        self.add_raw(ad_type, &data[..data_len])
+    }
 
-/// Advertising Interval
-#[allow(dead_code)]
-pub const ADV_TYPE_ADVERTISING_INTERVAL: u8 = 0x1A;
+    pub fn add_uuid32_list(self, complete: bool, uuids: &[u32]) -> AdvDataResult<Self> {
+        let ad_type = if complete {
+            ADV_TYPE_COMPLETE_LIST_32BIT_SERVICE_IDS
+        } else {
+            ADV_TYPE_INCOMPLETE_LIST_32BIT_SERVICE_IDS
+        };
+
+        let mut data = [0u8; ADV_DATA_LEN as usize];
+        let data_len = uuids.len() * 4;
+        if data_len > data.len() {
+            return Err(AdvDataError::Overflow);
+        }
+        for (chunk, uuid) in data.chunks_exact_mut(4).zip(uuids) {
+            chunk.copy_from_slice(&uuid.to_le_bytes());
+        }
+
+        self.add_raw(ad_type, &data[..data_len])
+    }
 
-/// LE Bluetooth Device Address
-#[allow(dead_code)]
-pub const ADV_TYPE_LE_BLUETOOTH_DEVICE_ADDRESS: u8 = 0x1B;
+    /// UUIDs are given in little endian byte order.
+    ///
+    /// Two 128-bit UUIDs exceed the 31 bytes of advertising data, any list with more than one
+    /// UUID is rejected with [`AdvDataError::Overflow`].
+    pub fn add_uuid128_list(self, complete: bool, uuids: &[[u8; 16]]) -> AdvDataResult<Self> {
+        let ad_type = if complete {
+            ADV_TYPE_COMPLETE_LIST_128BIT_SERVICE_IDS
+        } else {
+            ADV_TYPE_INCOMPLETE_LIST_128BIT_SERVICE_IDS
+        };
+
+        match uuids {
+            [] => self.add_raw(ad_type, &[]),
+            [uuid] => self.add_raw(ad_type, uuid),
+            // A second 128-bit UUID never fits into a single AD structure
+            _ => Err(AdvDataError::Overflow),
+        }
+    }
 
-/// LE Role
-#[allow(dead_code)]
-pub const ADV_TYPE_LE_ROLE: u8 = 0x1C;
+    pub fn add_service_data16(self, uuid: u16, data: &[u8]) -> AdvDataResult<Self> {
+        self.add_parts(
+            ADV_TYPE_SERVICE_DATA_16BIT_UUID,
+            &[&uuid.to_le_bytes(), data],
+        )
+    }
 
-/// Simple Pairing Hash C-256
-#[allow(dead_code)]
-pub const ADV_TYPE_SIMPLE_PAIRING_HASH_C256: u8 = 0x1D;
+    pub fn add_service_data32(self, uuid: u32, data: &[u8]) -> AdvDataResult<Self> {
+        self.add_parts(
+            ADV_TYPE_SERVICE_DATA_32BIT_UUID,
+            &[&uuid.to_le_bytes(), data],
+        )
+    }
 
-/// Simple Pairing Randomizer R-256
-#[allow(dead_code)]
-pub const ADV_TYPE_SIMPLE_PAIRING_RANDOM_C256: u8 = 0x1E;
+    /// `uuid` is given in little endian byte order
+    pub fn add_service_data128(self, uuid: &[u8; 16], data: &[u8]) -> AdvDataResult<Self> {
+        self.add_parts(ADV_TYPE_SERVICE_DATA_128BIT_UUID, &[uuid, data])
+    }
 
-/// Uniform Resource Identifier
-#[allow(dead_code)]
-pub const ADV_TYPE_URI: u8 = 0x24;
+    pub fn add_manufacturer_data(self, company_id: u16, data: &[u8]) -> AdvDataResult<Self> {
+        self.add_parts(
+            ADV_TYPE_MANUFACTURER_SPECIFIC_DATA,
+            &[&company_id.to_le_bytes(), data],
+        )
+    }
 
-/// 3D Information Data
-#[allow(dead_code)]
-pub const ADV_TYPE_INFO_DATA_3D: u8 = 0x3D;
+    /// TX power level in dBm
+    pub fn add_tx_power(self, tx_power: i8) -> AdvDataResult<Self> {
+        self.add_raw(ADV_TYPE_TX_POWER_LEVEL, &[tx_power as u8])
+    }
 
-/// Manufacturer Specific Data
-#[allow(dead_code)]
-pub const ADV_TYPE_MANUFACTURER_SPECIFIC_DATA: u8 = 0xFF;
+    pub fn add_appearance(self, appearance: u16) -> AdvDataResult<Self> {
+        self.add_raw(ADV_TYPE_APPEARANCE, &appearance.to_le_bytes())
+    }
 
-macro_rules! user_adv_data_entry_size {
-    ($byte:literal $($tail:literal)*) => {
-        1 + user_adv_data_entry_size!($($tail )*)
-    };
-    () => {
-        1
+    pub fn add_complete_name(self, name: &str) -> AdvDataResult<Self> {
+        self.add_raw(ADV_TYPE_COMPLETE_LOCAL_NAME, name.as_bytes())
     }
-}
 
-macro_rules! configure_user_adv_data {
-    ($({$adv_type: ident, $($byte:literal),*}),*) => {
-        pub static mut USER_ADVERTISE_DATA: [u8; 0 $(
-            + user_adv_data_entry_size!($($byte )*) + 1
-        )*] = [
-            $(
-                user_adv_data_entry_size!($($byte )*),
-                $adv_type,
-                $(
-                    $byte,
-                )*
-            )*
-        ];
-    };
+    pub fn add_shortened_name(self, name: &str) -> AdvDataResult<Self> {
+        self.add_raw(ADV_TYPE_SHORTENED_LOCAL_NAME, name.as_bytes())
+    }
 }
 
-macro_rules! configure_user_scan_response_data {
-    ($({$adv_type: ident, $($byte:literal),*}),*) => {
-        const USER_ADVERTISE_SCAN_RESPONSE_DATA: [u8; 0 $(
-            + user_adv_data_entry_size!($($byte )*) + 1
-        )*] = [
-            $(
-                user_adv_data_entry_size!($($byte )*),
-                $adv_type,
-                $(
-                    $byte,
-                )*
-            )*
-        ];
-    };
+impl Default for AdvData {
+    fn default() -> Self {
+        Self::new()
+    }
 }
-
-pub(crate) use user_adv_data_entry_size;
-pub(crate) use configure_user_adv_data;
-pub(crate) use configure_user_scan_response_data;
\ No newline at end of file
@@ -0,0 +1,392 @@
+//! Encoders for iBeacon and Eddystone frames.
+//!
+//! The frames are appended to [`AdvData`] so that they can be combined with further AD
+//! structures and passed to [`set_user_advertise_data`](super::set_user_advertise_data) or
+//! [`app_easy_gap_update_advertise_data`](super::app_easy_gap_update_advertise_data). The flags are
+//! not part of the frames, the stack adds them by itself when advertising in a discoverable mode.
+
+use super::advertise::{AdvData, AdvDataError, AdvDataResult};
+
+/// Company identifier of Apple Inc.
+const IBEACON_COMPANY_ID: u16 = 0x004c;
+/// iBeacon type and length of the remaining data
+const IBEACON_PREFIX: [u8; 2] = [0x02, 0x15];
+
+/// 16-bit service UUID of Eddystone
+pub const EDDYSTONE_UUID: u16 = 0xfeaa;
+
+const EDDYSTONE_FRAME_TYPE_UID: u8 = 0x00;
+const EDDYSTONE_FRAME_TYPE_URL: u8 = 0x10;
+const EDDYSTONE_FRAME_TYPE_TLM: u8 = 0x20;
+
+/// Longest encoded URL, excluding the scheme prefix
+const EDDYSTONE_URL_MAX_LEN: usize = 17;
+
+const EDDYSTONE_URL_SCHEMES: [&str; 4] = ["http://www.", "https://www.", "http://", "https://"];
+
+/// Expansions in the order of their codes, each one with a trailing slash precedes the same one
+/// without so that the longer one is preferred
+const EDDYSTONE_URL_EXPANSIONS: [&str; 14] = [
+    ".com/", ".org/", ".edu/", ".net/", ".info/", ".biz/", ".gov/", ".com", ".org", ".edu", ".net",
+    ".info", ".biz", ".gov",
+];
+
+/// Temperature of a TLM frame if the beacon does not support measuring it
+pub const EDDYSTONE_TLM_TEMPERATURE_UNSUPPORTED: i16 = i16::MIN;
+
+#[derive(Debug)]
+pub enum BeaconError {
+    /// The URL does not start with a scheme which can be encoded
+    UnsupportedScheme,
+    /// The URL contains a character which cannot be encoded
+    InvalidCharacter,
+    /// The encoded URL is longer than 17 bytes
+    UrlTooLong,
+    /// The frame does not fit into the remaining space
+    Overflow,
+}
+
+impl From<AdvDataError> for BeaconError {
+    fn from(error: AdvDataError) -> Self {
+        match error {
+            AdvDataError::Overflow => BeaconError::Overflow,
+        }
+    }
+}
+
+pub type BeaconResult<T = ()> = Result<T, BeaconError>;
+
+/// Telemetry of an unencrypted Eddystone-TLM frame.
+///
+/// Battery voltage and temperature are typically measured with the GPADC and corrected with the
+/// trim values of [`otp_cs`](crate::platform::otp_cs).
+#[derive(Debug, Clone, Copy)]
+pub struct EddystoneTlm {
+    /// Battery voltage in mV, 0 if not supported
+    pub battery_mv: u16,
+    /// Temperature in degrees Celsius as signed 8.8 fixed point number, or
+    /// [`EDDYSTONE_TLM_TEMPERATURE_UNSUPPORTED`]
+    pub temperature: i16,
+    /// Number of advertising frames sent since power-up or reboot
+    pub adv_count: u32,
+    /// Time since power-up or reboot in units of 0.1 s
+    pub uptime: u32,
+}
+
+/// Encodes `url` as scheme prefix and compressed URL of an Eddystone-URL frame.
+fn eddystone_url_encode(
+    url: &str,
+    encoded: &mut [u8; 1 + EDDYSTONE_URL_MAX_LEN],
+) -> BeaconResult<usize> {
+    // Schemes with "www." precede the ones without
+    let (scheme, mut rest) = EDDYSTONE_URL_SCHEMES
+        .iter()
+        .enumerate()
+        .find_map(|(code, scheme)| Some((code as u8, url.strip_prefix(scheme)?)))
+        .ok_or(BeaconError::UnsupportedScheme)?;
+
+    encoded[0] = scheme;
+    let mut len = 1;
+
+    while !rest.is_empty() {
+        if len == encoded.len() {
+            return Err(BeaconError::UrlTooLong);
+        }
+
+        let expansion = EDDYSTONE_URL_EXPANSIONS
+            .iter()
+            .enumerate()
+            .find_map(|(code, expansion)| Some((code as u8, rest.strip_prefix(expansion)?)));
+
+        if let Some((code, tail)) = expansion {
+            encoded[len] = code;
+            rest = tail;
+        } else {
+            let c = rest.as_bytes()[0];
+
+            // Codes below 0x21 are expansions, codes above 0x7e are reserved
+            if !(0x21..=0x7e).contains(&c) {
+                return Err(BeaconError::InvalidCharacter);
+            }
+
+            encoded[len] = c;
+            rest = &rest[1..];
+        }
+
+        len += 1;
+    }
+
+    Ok(len)
+}
+
+impl AdvData {
+    /// Appends an iBeacon frame.
+    ///
+    /// `uuid` is given in the order it is transmitted, i.e. as it is written in its string
+    /// representation. `measured_power` is the RSSI at 1 m in dBm.
+    pub fn add_ibeacon(
+        self,
+        uuid: &[u8; 16],
+        major: u16,
+        minor: u16,
+        measured_power: i8,
+    ) -> AdvDataResult<Self> {
+        let mut data = [0u8; 23];
+
+        data[..2].copy_from_slice(&IBEACON_PREFIX);
+        data[2..18].copy_from_slice(uuid);
+        data[18..20].copy_from_slice(&major.to_be_bytes());
+        data[20..22].copy_from_slice(&minor.to_be_bytes());
+        data[22] = measured_power as u8;
+
+        self.add_manufacturer_data(IBEACON_COMPANY_ID, &data)
+    }
+
+    fn add_eddystone_frame(self, frame: &[u8]) -> AdvDataResult<Self> {
+        self.add_uuid16_list(true, &[EDDYSTONE_UUID])?
+            .add_service_data16(EDDYSTONE_UUID, frame)
+    }
+
+    /// Appends an Eddystone-UID frame, `tx_power` is the TX power at 0 m in dBm.
+    pub fn add_eddystone_uid(
+        self,
+        tx_power: i8,
+        namespace: &[u8; 10],
+        instance: &[u8; 6],
+    ) -> AdvDataResult<Self> {
+        let mut frame = [0u8; 20];
+
+        frame[0] = EDDYSTONE_FRAME_TYPE_UID;
+        frame[1] = tx_power as u8;
+        frame[2..12].copy_from_slice(namespace);
+        frame[12..18].copy_from_slice(instance);
+        // The last two bytes are reserved
+
+        self.add_eddystone_frame(&frame)
+    }
+
+    /// Appends an Eddystone-URL frame, `tx_power` is the TX power at 0 m in dBm.
+    ///
+    /// The URL has to start with one of the schemes `http://www.`, `https://www.`, `http://` or
+    /// `https://`, common top-level domains are compressed.
+    pub fn add_eddystone_url(self, tx_power: i8, url: &str) -> BeaconResult<Self> {
+        let mut frame = [0u8; 2 + 1 + EDDYSTONE_URL_MAX_LEN];
+
+        frame[0] = EDDYSTONE_FRAME_TYPE_URL;
+        frame[1] = tx_power as u8;
+        let len = eddystone_url_encode(url, (&mut frame[2..]).try_into().unwrap())?;
+
+        Ok(self.add_eddystone_frame(&frame[..2 + len])?)
+    }
+
+    /// Appends an unencrypted Eddystone-TLM frame.
+    pub fn add_eddystone_tlm(self, tlm: &EddystoneTlm) -> AdvDataResult<Self> {
+        let mut frame = [0u8; 14];
+
+        frame[0] = EDDYSTONE_FRAME_TYPE_TLM;
+        // Version of the unencrypted frame
+        frame[1] = 0x00;
+        frame[2..4].copy_from_slice(&tlm.battery_mv.to_be_bytes());
+        frame[4..6].copy_from_slice(&tlm.temperature.to_be_bytes());
+        frame[6..10].copy_from_slice(&tlm.adv_count.to_be_bytes());
+        frame[10..14].copy_from_slice(&tlm.uptime.to_be_bytes());
+
+        self.add_eddystone_frame(&frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UUID: [u8; 16] = [
+        0xe2, 0xc5, 0x6d, 0xb5, 0xdf, 0xfb, 0x48, 0xd2, 0xb0, 0x60, 0xd0, 0xf5, 0xa7, 0x10, 0x96,
+        0xe0,
+    ];
+
+    fn url_frame(url: &str) -> BeaconResult<AdvData> {
+        AdvData::new().add_eddystone_url(-20, url)
+    }
+
+    /// Service data of the Eddystone-URL frame following the UUID list
+    fn encoded_url(url: &str) -> [u8; 31] {
+        let data = url_frame(url).unwrap();
+        let bytes = data.as_bytes();
+
+        assert_eq!(bytes[..4], [0x03, 0x03, 0xaa, 0xfe]);
+        assert_eq!(bytes[4] as usize, bytes.len() - 5);
+        assert_eq!(bytes[5..10], [0x16, 0xaa, 0xfe, 0x10, 0xec]);
+
+        let mut encoded = [0; 31];
+        encoded[..bytes.len() - 10].copy_from_slice(&bytes[10..]);
+        encoded
+    }
+
+    #[test]
+    fn ibeacon() {
+        let data = AdvData::new()
+            .add_ibeacon(&UUID, 0x0102, 0x0304, -59)
+            .unwrap();
+
+        let mut expected = [0u8; 27];
+        expected[..6].copy_from_slice(&[0x1a, 0xff, 0x4c, 0x00, 0x02, 0x15]);
+        expected[6..22].copy_from_slice(&UUID);
+        expected[22..].copy_from_slice(&[0x01, 0x02, 0x03, 0x04, 0xc5]);
+
+        assert_eq!(data.as_bytes(), expected);
+    }
+
+    #[test]
+    fn ibeacon_overflow() {
+        let data = AdvData::new().add_complete_name("DA14531").unwrap();
+
+        assert!(matches!(
+            data.add_ibeacon(&UUID, 0, 0, -59),
+            Err(AdvDataError::Overflow)
+        ));
+    }
+
+    #[test]
+    fn eddystone_uid() {
+        let namespace = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09];
+        let instance = [0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5];
+
+        let data = AdvData::new()
+            .add_eddystone_uid(-20, &namespace, &instance)
+            .unwrap();
+
+        let mut expected = [0u8; 28];
+        expected[..10]
+            .copy_from_slice(&[0x03, 0x03, 0xaa, 0xfe, 0x17, 0x16, 0xaa, 0xfe, 0x00, 0xec]);
+        expected[10..20].copy_from_slice(&namespace);
+        expected[20..26].copy_from_slice(&instance);
+
+        assert_eq!(data.as_bytes(), expected);
+    }
+
+    #[test]
+    fn eddystone_url_schemes() {
+        assert_eq!(encoded_url("http://www.a")[..2], [0x00, b'a']);
+        assert_eq!(encoded_url("https://www.a")[..2], [0x01, b'a']);
+        assert_eq!(encoded_url("http://a")[..2], [0x02, b'a']);
+        assert_eq!(encoded_url("https://a")[..2], [0x03, b'a']);
+    }
+
+    #[test]
+    fn eddystone_url_unsupported_scheme() {
+        assert!(matches!(
+            url_frame("ftp://example.com"),
+            Err(BeaconError::UnsupportedScheme)
+        ));
+        assert!(matches!(
+            url_frame("www.example.com"),
+            Err(BeaconError::UnsupportedScheme)
+        ));
+    }
+
+    #[test]
+    fn eddystone_url_without_expansion() {
+        let data = url_frame("https://goo.gl/S6zT6P").unwrap();
+
+        assert_eq!(
+            data.as_bytes(),
+            [
+                0x03, 0x03, 0xaa, 0xfe, 0x13, 0x16, 0xaa, 0xfe, 0x10, 0xec, 0x03, b'g', b'o', b'o',
+                b'.', b'g', b'l', b'/', b'S', b'6', b'z', b'T', b'6', b'P',
+            ]
+        );
+    }
+
+    #[test]
+    fn eddystone_url_expansions() {
+        assert_eq!(
+            encoded_url("http://www.example.com/")[..9],
+            [0x00, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0x00]
+        );
+        assert_eq!(
+            encoded_url("https://example.org")[..9],
+            [0x03, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0x08]
+        );
+        assert_eq!(
+            encoded_url("http://a.info/b")[..4],
+            [0x02, b'a', 0x04, b'b']
+        );
+        assert_eq!(encoded_url("http://a.gov")[..3], [0x02, b'a', 0x0d]);
+        assert_eq!(
+            encoded_url("http://a.net/b.edu")[..5],
+            [0x02, b'a', 0x03, b'b', 0x09]
+        );
+    }
+
+    #[test]
+    fn eddystone_url_length_limit() {
+        // 17 characters after the scheme
+        let longest = "https://abcdefghijklmnopq";
+        // Expansions count as a single byte
+        let compressed = "https://abcdefghijklmnop.com/";
+
+        assert_eq!(url_frame(longest).unwrap().len(), 10 + 1 + 17);
+        assert_eq!(url_frame(compressed).unwrap().len(), 10 + 1 + 17);
+        assert!(matches!(
+            url_frame("https://abcdefghijklmnopqr"),
+            Err(BeaconError::UrlTooLong)
+        ));
+    }
+
+    #[test]
+    fn eddystone_url_invalid_characters() {
+        assert!(matches!(
+            url_frame("https://a b"),
+            Err(BeaconError::InvalidCharacter)
+        ));
+        assert!(matches!(
+            url_frame("https://caf\u{e9}"),
+            Err(BeaconError::InvalidCharacter)
+        ));
+        assert!(matches!(
+            url_frame("https://a\u{7f}"),
+            Err(BeaconError::InvalidCharacter)
+        ));
+    }
+
+    #[test]
+    fn eddystone_tlm() {
+        let data = AdvData::new()
+            .add_eddystone_tlm(&EddystoneTlm {
+                battery_mv: 3000,
+                // 25.5 °C
+                temperature: 0x1980,
+                adv_count: 0x0102_0304,
+                uptime: 0x0a0b_0c0d,
+            })
+            .unwrap();
+
+        assert_eq!(
+            data.as_bytes(),
+            [
+                0x03, 0x03, 0xaa, 0xfe, 0x11, 0x16, 0xaa, 0xfe, 0x20, 0x00, 0x0b, 0xb8, 0x19, 0x80,
+                0x01, 0x02, 0x03, 0x04, 0x0a, 0x0b, 0x0c, 0x0d,
+            ]
+        );
+    }
+
+    #[test]
+    fn eddystone_tlm_temperature() {
+        let tlm = |temperature| {
+            let data = AdvData::new()
+                .add_eddystone_tlm(&EddystoneTlm {
+                    battery_mv: 0,
+                    temperature,
+                    adv_count: 0,
+                    uptime: 0,
+                })
+                .unwrap();
+
+            [data.as_bytes()[12], data.as_bytes()[13]]
+        };
+
+        // -1.5 °C
+        assert_eq!(tlm(-0x0180), [0xfe, 0x80]);
+        assert_eq!(tlm(EDDYSTONE_TLM_TEMPERATURE_UNSUPPORTED), [0x80, 0x00]);
+    }
+}
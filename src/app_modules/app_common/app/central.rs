@@ -0,0 +1,193 @@
+//! Initiating connections in the central role.
+//!
+//! The scan and connection parameters are taken from a [`CentralConfiguration`]. The name
+//! requested with [`app_easy_gap_start_name_request`] is received as
+//! [`KeMsgGapmPeerNameInd`](crate::ble_stack::host::gap::gapm::task::KeMsgGapmPeerNameInd), which
+//! can be handled with [`register_msg_handler`](crate::app_modules::msg_dispatch::register_msg_handler).
+
+use crate::{
+    app_modules::{CentralConfiguration, PeerAddress},
+    ble_stack::host::gap::{
+        gapm::task::{
+            KeMsgGapmStartConnectionCmd, GAPM_CONNECTION_AUTO, GAPM_CONNECTION_DIRECT,
+            GAPM_CONNECTION_NAME_REQUEST,
+        },
+        GapBDAddr,
+    },
+    platform::core_modules::{
+        common::BDAddr,
+        rwip::{TASK_APP, TASK_GAPM},
+    },
+};
+
+use super::app_easy_gap_cancel;
+
+#[derive(Debug)]
+pub enum CentralError {
+    /// The peer list is empty
+    NoPeers,
+    /// The peer list does not fit into a single command
+    TooManyPeers,
+    /// The operation code of the configuration is not a supported connection operation
+    UnsupportedOperation,
+}
+
+pub type CentralResult<T = ()> = Result<T, CentralError>;
+
+impl From<&PeerAddress> for GapBDAddr {
+    fn from(peer: &PeerAddress) -> Self {
+        GapBDAddr {
+            addr: BDAddr {
+                addr: peer.peer_addr,
+            },
+            addr_type: peer.peer_addr_type,
+        }
+    }
+}
+
+fn is_configured(peer: &PeerAddress) -> bool {
+    peer.peer_addr != [0; 6]
+}
+
+fn app_easy_gap_start_connection_to_create_msg(
+    config: &CentralConfiguration,
+    code: u8,
+    peers: &[GapBDAddr],
+) -> CentralResult<KeMsgGapmStartConnectionCmd> {
+    if peers.is_empty() {
+        return Err(CentralError::NoPeers);
+    }
+
+    let nb_peers: u8 = peers
+        .len()
+        .try_into()
+        .map_err(|_| CentralError::TooManyPeers)?;
+
+    let mut cmd = KeMsgGapmStartConnectionCmd::new_dynamic(
+        core::mem::size_of_val(peers) as u16,
+        TASK_APP as u16,
+        TASK_GAPM as u16,
+    );
+
+    let msg = cmd.fields();
+
+    msg.op.code = code;
+    msg.op.addr_src = config.addr_src;
+    msg.scan_interval = config.scan_interval;
+    msg.scan_window = config.scan_window;
+    msg.con_intv_min = config.con_intv_min;
+    msg.con_intv_max = config.con_intv_max;
+    msg.con_latency = config.con_latency;
+    msg.superv_to = config.superv_to;
+    msg.ce_len_min = config.ce_len_min;
+    msg.ce_len_max = config.ce_len_max;
+    msg.nb_peers = nb_peers;
+
+    for (entry, peer) in cmd.tail().iter_mut().zip(peers) {
+        *entry = *peer;
+    }
+
+    Ok(cmd)
+}
+
+/// Starts the operation selected by `config.code`.
+///
+/// `GAPM_CONNECTION_DIRECT` and `GAPM_CONNECTION_NAME_REQUEST` address the first peer of
+/// `config.peer_addresses`, `GAPM_CONNECTION_AUTO` connects to the first one of all of them which
+/// is found. Entries with an all-zero address are not configured and skipped.
+pub fn app_easy_gap_start_connection_to(config: &CentralConfiguration) -> CentralResult {
+    let code = config.code as u32;
+
+    let mut peers = [GapBDAddr::from(&config.peer_addresses[0]); 8];
+    let mut nb_peers = 0;
+
+    for peer in &config.peer_addresses {
+        if is_configured(peer) {
+            peers[nb_peers] = GapBDAddr::from(peer);
+            nb_peers += 1;
+        }
+    }
+
+    if code == GAPM_CONNECTION_DIRECT || code == GAPM_CONNECTION_NAME_REQUEST {
+        let peers = &peers[..nb_peers.min(1)];
+
+        app_easy_gap_start_connection_to_create_msg(config, config.code, peers)?.send();
+    } else if code == GAPM_CONNECTION_AUTO {
+        let peers = &peers[..nb_peers];
+
+        app_easy_gap_start_connection_to_create_msg(config, config.code, peers)?.send();
+    } else {
+        return Err(CentralError::UnsupportedOperation);
+    }
+
+    Ok(())
+}
+
+/// Connects to `peer` with a fixed connection interval of `intv`, the remaining parameters are
+/// taken from `config`.
+pub fn app_easy_gap_start_connection_to_set(
+    config: &CentralConfiguration,
+    peer: &GapBDAddr,
+    intv: u16,
+) -> CentralResult {
+    let mut cmd = app_easy_gap_start_connection_to_create_msg(
+        config,
+        GAPM_CONNECTION_DIRECT as u8,
+        core::slice::from_ref(peer),
+    )?;
+
+    let msg = cmd.fields();
+    msg.con_intv_min = intv;
+    msg.con_intv_max = intv;
+
+    cmd.send();
+
+    Ok(())
+}
+
+/// Connects to `peer`, which has to advertise connectable.
+pub fn app_easy_gap_start_connection_direct(
+    config: &CentralConfiguration,
+    peer: &GapBDAddr,
+) -> CentralResult {
+    app_easy_gap_start_connection_to_create_msg(
+        config,
+        GAPM_CONNECTION_DIRECT as u8,
+        core::slice::from_ref(peer),
+    )?
+    .send();
+
+    Ok(())
+}
+
+/// Connects to the first device of `peers` which is found advertising.
+///
+/// The peers are added to the white list of the controller.
+pub fn app_easy_gap_start_connection_auto(
+    config: &CentralConfiguration,
+    peers: &[GapBDAddr],
+) -> CentralResult {
+    app_easy_gap_start_connection_to_create_msg(config, GAPM_CONNECTION_AUTO as u8, peers)?.send();
+
+    Ok(())
+}
+
+/// Connects to `peer` to read its device name and disconnects afterwards.
+pub fn app_easy_gap_start_name_request(
+    config: &CentralConfiguration,
+    peer: &GapBDAddr,
+) -> CentralResult {
+    app_easy_gap_start_connection_to_create_msg(
+        config,
+        GAPM_CONNECTION_NAME_REQUEST as u8,
+        core::slice::from_ref(peer),
+    )?
+    .send();
+
+    Ok(())
+}
+
+/// Cancels establishing a connection.
+pub fn app_easy_gap_cancel_connection() {
+    app_easy_gap_cancel();
+}
@@ -0,0 +1,174 @@
+//! Active connections and their state.
+//!
+//! The peer address is tracked by the SDK in `app_env`, the connection parameters and the RSSI
+//! are tracked from the connection, parameter update and RSSI indications.
+
+use crate::{
+    app_modules::app::APP_EASY_MAX_ACTIVE_CONNECTION,
+    ble_stack::host::{
+        gap::{
+            gapc::{
+                gapc_lk_sec_lvl_get,
+                task::{GapcConRssiInd, GapcConnectionReqInd, GapcParamUpdatedInd},
+            },
+            GapBDAddr, GAP_INVALID_CONIDX, GAP_LK_AUTH, GAP_LK_SEC_CON, GAP_LK_UNAUTH,
+        },
+        gatt::gattc::gattc_get_mtu,
+    },
+    platform::core_modules::common::BDAddr,
+};
+
+use super::app_env;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SecurityLevel {
+    /// No encryption
+    NoSecurity,
+    /// Encrypted with an unauthenticated key
+    Unauthenticated,
+    /// Encrypted with an authenticated key
+    Authenticated,
+    /// Encrypted with an authenticated LE Secure Connections key
+    SecureConnections,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phy {
+    Le1M,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TrackedState {
+    /// Connection interval in units of 1.25 ms
+    interval: u16,
+    latency: u16,
+    /// Supervision timeout in units of 10 ms
+    supervision_timeout: u16,
+    rssi: Option<i8>,
+}
+
+const NO_TRACKED_STATE: TrackedState = TrackedState {
+    interval: 0,
+    latency: 0,
+    supervision_timeout: 0,
+    rssi: None,
+};
+
+#[link_section = "retention_mem_area0"]
+static mut TRACKED_STATES: [TrackedState; APP_EASY_MAX_ACTIVE_CONNECTION as usize] =
+    [NO_TRACKED_STATE; APP_EASY_MAX_ACTIVE_CONNECTION as usize];
+
+/// Snapshot of an active connection.
+#[derive(Debug, Clone, Copy)]
+pub struct Connection {
+    pub conidx: u8,
+    pub conhdl: u16,
+    pub peer_addr: GapBDAddr,
+    /// Connection interval in units of 1.25 ms
+    pub interval: u16,
+    pub latency: u16,
+    /// Supervision timeout in units of 10 ms
+    pub supervision_timeout: u16,
+    /// Most recently received RSSI in dBm
+    pub rssi: Option<i8>,
+}
+
+impl Connection {
+    pub fn mtu(&self) -> u16 {
+        gattc_get_mtu(self.conidx)
+    }
+
+    /// The DA14531 supports the LE 1M PHY only
+    pub fn phy(&self) -> Phy {
+        Phy::Le1M
+    }
+
+    pub fn security_level(&self) -> SecurityLevel {
+        let level = gapc_lk_sec_lvl_get(self.conidx) as u32;
+
+        if level == GAP_LK_UNAUTH {
+            SecurityLevel::Unauthenticated
+        } else if level == GAP_LK_AUTH {
+            SecurityLevel::Authenticated
+        } else if level == GAP_LK_SEC_CON {
+            SecurityLevel::SecureConnections
+        } else {
+            SecurityLevel::NoSecurity
+        }
+    }
+}
+
+/// Active connection with index `conidx`
+pub fn get(conidx: u8) -> Option<Connection> {
+    let env = unsafe { app_env.get(conidx as usize)? };
+
+    if !env.connection_active || env.conidx == GAP_INVALID_CONIDX as u8 {
+        return None;
+    }
+
+    let state = unsafe { TRACKED_STATES[conidx as usize] };
+
+    Some(Connection {
+        conidx,
+        conhdl: env.conhdl,
+        peer_addr: GapBDAddr {
+            addr: BDAddr {
+                addr: env.peer_addr.addr,
+            },
+            addr_type: env.peer_addr_type,
+        },
+        interval: state.interval,
+        latency: state.latency,
+        supervision_timeout: state.supervision_timeout,
+        rssi: state.rssi,
+    })
+}
+
+/// All active connections
+pub fn iter() -> impl Iterator<Item = Connection> {
+    (0..APP_EASY_MAX_ACTIVE_CONNECTION as u8).filter_map(get)
+}
+
+/// Active connection to the peer with address `addr`, regardless of the address type
+pub fn find_by_addr(addr: &BDAddr) -> Option<Connection> {
+    iter().find(|connection| connection.peer_addr.addr.addr == addr.addr)
+}
+
+pub fn count() -> usize {
+    iter().count()
+}
+
+fn tracked_state(conidx: u8) -> Option<&'static mut TrackedState> {
+    unsafe { TRACKED_STATES.get_mut(conidx as usize) }
+}
+
+pub(crate) fn on_connection(conidx: u8, param: &GapcConnectionReqInd) {
+    if let Some(state) = tracked_state(conidx) {
+        *state = TrackedState {
+            interval: param.con_interval,
+            latency: param.con_latency,
+            supervision_timeout: param.sup_to,
+            rssi: None,
+        };
+    }
+}
+
+pub(crate) fn on_disconnect(conidx: u8) {
+    if let Some(state) = tracked_state(conidx) {
+        *state = NO_TRACKED_STATE;
+    }
+}
+
+pub(crate) fn on_param_updated_ind(conidx: u8, param: &GapcParamUpdatedInd) {
+    if let Some(state) = tracked_state(conidx) {
+        state.interval = param.con_interval;
+        state.latency = param.con_latency;
+        state.supervision_timeout = param.sup_to;
+    }
+}
+
+pub(crate) fn on_con_rssi_ind(conidx: u8, param: &GapcConRssiInd) {
+    if let Some(state) = tracked_state(conidx) {
+        state.rssi = Some(param.rssi);
+    }
+}
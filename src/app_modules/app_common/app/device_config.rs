@@ -0,0 +1,277 @@
+//! Configuration of the GAP manager.
+//!
+//! The configuration is applied by [`app_easy_gap_dev_configure`](super::app_easy_gap_dev_configure),
+//! which is called by the SDK once the stack has been reset, at startup as well as after
+//! [`app_easy_gap_reconfigure`].
+
+use bitflags::bitflags;
+
+use crate::{
+    ble_stack::host::gap::gapm::task::{
+        KeMsgGapmResetCmd, GAPM_MASK_ATT_APPEARENCE_PERM, GAPM_MASK_ATT_NAME_PERM,
+        GAPM_MASK_ATT_SLV_PREF_CON_PAR_EN, GAPM_MASK_ATT_SVC_CHG_EN, GAPM_POS_ATT_APPEARENCE_PERM,
+        GAPM_POS_ATT_NAME_PERM, GAPM_RESET, GAPM_WRITE_AUTH, GAPM_WRITE_DISABLE, GAPM_WRITE_ENABLE,
+        GAPM_WRITE_SECURE, GAPM_WRITE_UNAUTH,
+    },
+    platform::core_modules::{
+        common::BDAddr,
+        rwip::{TASK_APP, TASK_GAPM},
+    },
+};
+
+use super::{
+    connections,
+    link::{self, LinkConfigError, LinkConfiguration},
+    peer_info, scan, GapRole,
+};
+
+bitflags! {
+    /// Presence of optional attributes of the GAP and GATT services, `GAPM_MASK_ATT_*`.
+    pub struct AttConfig: u8 {
+        /// The peripheral preferred connection parameters are present
+        const SLV_PREF_CON_PAR_EN = GAPM_MASK_ATT_SLV_PREF_CON_PAR_EN as u8;
+        /// The service changed characteristic is present
+        const SVC_CHG_EN = GAPM_MASK_ATT_SVC_CHG_EN as u8;
+    }
+}
+
+/// Write permission of the device name and the appearance attributes, `gapm_write_att_perm`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WritePerm {
+    /// The attribute is read only
+    Disabled,
+    /// Writable without security requirements
+    Enabled,
+    /// Writable over an unauthenticated encrypted link
+    Unauth,
+    /// Writable over an authenticated encrypted link
+    Auth,
+    /// Writable over a link encrypted with LE secure connections
+    Secure,
+}
+
+impl From<WritePerm> for u8 {
+    fn from(perm: WritePerm) -> Self {
+        (match perm {
+            WritePerm::Disabled => GAPM_WRITE_DISABLE,
+            WritePerm::Enabled => GAPM_WRITE_ENABLE,
+            WritePerm::Unauth => GAPM_WRITE_UNAUTH,
+            WritePerm::Auth => GAPM_WRITE_AUTH,
+            WritePerm::Secure => GAPM_WRITE_SECURE,
+        }) as u8
+    }
+}
+
+#[derive(Debug)]
+pub enum DeviceConfigError {
+    /// The renew duration of the private address is 0
+    InvalidRenewDuration,
+    /// The GAP and GATT services are configured with the same start handle
+    OverlappingStartHandles,
+    /// The address is not a valid random static address
+    InvalidStaticAddr,
+    /// The MTU or data length limits are invalid
+    InvalidLinkConfig(LinkConfigError),
+    /// The device cannot be reconfigured while connected
+    Connected,
+}
+
+impl From<LinkConfigError> for DeviceConfigError {
+    fn from(error: LinkConfigError) -> Self {
+        DeviceConfigError::InvalidLinkConfig(error)
+    }
+}
+
+pub type DeviceConfigResult<T = ()> = Result<T, DeviceConfigError>;
+
+#[derive(Debug, Clone)]
+pub struct DeviceConfiguration {
+    pub(super) role: GapRole,
+    pub(super) renew_dur: u16,
+    pub(super) irk: [u8; 16],
+    pub(super) static_addr: Option<BDAddr>,
+    pub(super) att_cfg: AttConfig,
+    pub(super) name_perm: WritePerm,
+    pub(super) appearance_perm: WritePerm,
+    pub(super) gap_start_hdl: u16,
+    pub(super) gatt_start_hdl: u16,
+    pub(super) link: LinkConfiguration,
+}
+
+impl DeviceConfiguration {
+    /// Peripheral with the service changed characteristic, dynamically allocated start handles
+    /// and the default link configuration.
+    pub const fn new() -> Self {
+        Self {
+            role: GapRole::Peripheral,
+            renew_dur: 15000,
+            irk: [
+                0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+                0x0e, 0x0f,
+            ],
+            static_addr: None,
+            att_cfg: AttConfig::SVC_CHG_EN,
+            name_perm: WritePerm::Disabled,
+            appearance_perm: WritePerm::Disabled,
+            gap_start_hdl: 0,
+            gatt_start_hdl: 0,
+            link: LinkConfiguration::new(),
+        }
+    }
+
+    pub fn set_role(mut self, role: GapRole) -> Self {
+        self.role = role;
+        self
+    }
+
+    /// Duration after which the private address is renewed in units of 10 ms, only used if
+    /// neither the public nor the static address mode is selected
+    pub fn set_renew_dur(mut self, renew_dur: u16) -> Self {
+        self.renew_dur = renew_dur;
+        self
+    }
+
+    /// Identity resolving key, only used if neither the public nor the static address mode is
+    /// selected
+    pub fn set_irk(mut self, irk: [u8; 16]) -> Self {
+        self.irk = irk;
+        self
+    }
+
+    /// Random static address used in the static address mode, `app_on_generate_static_random_addr`
+    /// is called to generate one if none is set
+    pub fn set_static_addr(mut self, static_addr: Option<BDAddr>) -> Self {
+        self.static_addr = static_addr;
+        self
+    }
+
+    pub fn set_att_cfg(mut self, att_cfg: AttConfig) -> Self {
+        self.att_cfg = att_cfg;
+        self
+    }
+
+    /// Write permission of the device name
+    pub fn set_name_perm(mut self, name_perm: WritePerm) -> Self {
+        self.name_perm = name_perm;
+        self
+    }
+
+    /// Write permission of the appearance
+    pub fn set_appearance_perm(mut self, appearance_perm: WritePerm) -> Self {
+        self.appearance_perm = appearance_perm;
+        self
+    }
+
+    /// Start handle of the GAP service, 0 for dynamic allocation
+    pub fn set_gap_start_hdl(mut self, gap_start_hdl: u16) -> Self {
+        self.gap_start_hdl = gap_start_hdl;
+        self
+    }
+
+    /// Start handle of the GATT service, 0 for dynamic allocation
+    pub fn set_gatt_start_hdl(mut self, gatt_start_hdl: u16) -> Self {
+        self.gatt_start_hdl = gatt_start_hdl;
+        self
+    }
+
+    /// MTU and data length limits
+    pub fn set_link(mut self, link: LinkConfiguration) -> Self {
+        self.link = link;
+        self
+    }
+
+    /// `att_cfg` of the `GAPM_SET_DEV_CONFIG_CMD`
+    pub(super) fn att_cfg_bits(&self) -> u8 {
+        let name_perm = (u8::from(self.name_perm) << GAPM_POS_ATT_NAME_PERM) as u32;
+        let appearance_perm =
+            (u8::from(self.appearance_perm) << GAPM_POS_ATT_APPEARENCE_PERM) as u32;
+
+        self.att_cfg.bits()
+            | (name_perm & GAPM_MASK_ATT_NAME_PERM) as u8
+            | (appearance_perm & GAPM_MASK_ATT_APPEARENCE_PERM) as u8
+    }
+
+    fn validate(&self) -> DeviceConfigResult {
+        // The private address is only renewed if neither the public nor the static address mode
+        // is selected
+        #[cfg(not(any(feature = "address_mode_public", feature = "address_mode_static")))]
+        {
+            if self.renew_dur == 0 {
+                return Err(DeviceConfigError::InvalidRenewDuration);
+            }
+        }
+
+        if self.gap_start_hdl != 0 && self.gap_start_hdl == self.gatt_start_hdl {
+            return Err(DeviceConfigError::OverlappingStartHandles);
+        }
+
+        if let Some(static_addr) = &self.static_addr {
+            let addr = &static_addr.addr;
+
+            // The two most significant bits have to be set, the remaining ones must neither be
+            // all set nor all cleared
+            let all_set = addr.iter().all(|byte| *byte == 0xff);
+            let all_cleared = addr[..5].iter().all(|byte| *byte == 0) && addr[5] == GAP_STATIC_ADDR;
+
+            if addr[5] & GAP_STATIC_ADDR != GAP_STATIC_ADDR || all_set || all_cleared {
+                return Err(DeviceConfigError::InvalidStaticAddr);
+            }
+        }
+
+        self.link.validate()?;
+
+        Ok(())
+    }
+}
+
+impl Default for DeviceConfiguration {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Two most significant bits of a random static address
+const GAP_STATIC_ADDR: u8 = 0xc0;
+
+#[link_section = "retention_mem_area0"]
+pub(super) static mut DEVICE_CONFIG: DeviceConfiguration = DeviceConfiguration::new();
+
+/// Sets the configuration applied by the next device configuration, i.e. at startup.
+pub fn set_device_configuration(config: DeviceConfiguration) -> DeviceConfigResult {
+    config.validate()?;
+
+    unsafe {
+        DEVICE_CONFIG = config;
+    }
+
+    Ok(())
+}
+
+/// Resets the stack and configures the device with `config` afterwards.
+///
+/// The database of the profiles is initialized again once the configuration completes, as it is
+/// at startup. Pending parameter updates, queries and scanning fail with `GapError::Canceled`
+/// and the advertising timeout is stopped before the reset.
+pub fn app_easy_gap_reconfigure(config: DeviceConfiguration) -> DeviceConfigResult {
+    if connections::count() != 0 {
+        return Err(DeviceConfigError::Connected);
+    }
+
+    set_device_configuration(config)?;
+
+    super::on_param_update_reset();
+    peer_info::on_reset();
+    link::on_reset();
+    scan::on_reset();
+
+    #[cfg(not(feature = "exclude_dlg_timer"))]
+    super::app_easy_gap_advertise_with_timeout_stop();
+
+    let mut cmd = KeMsgGapmResetCmd::new(TASK_APP as u16, TASK_GAPM as u16);
+
+    cmd.fields().operation = GAPM_RESET as u8;
+
+    cmd.send();
+
+    Ok(())
+}
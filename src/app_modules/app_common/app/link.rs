@@ -0,0 +1,310 @@
+//! MTU and data length extension.
+//!
+//! The limits of [`LinkConfiguration`] are set with
+//! [`DeviceConfiguration::set_link`](super::device_config::DeviceConfiguration::set_link). The data
+//! length results are only delivered if
+//! [`default_app_on_data_length_change`](crate::app_modules::default_app_on_data_length_change)
+//! is called from `app_on_data_length_change`.
+//!
+//! Requests are refused and fail like the queries of [`peer_info`](super::peer_info).
+
+use crate::{
+    ble_stack::{
+        host::{
+            gap::gapc::task::{GapcLePktSizeInd, KeMsgGapcSetLePktSizeCmd, GAPC_SET_LE_PKT_SIZE},
+            gatt::gattc::task::{GattcMtuChangedInd, KeMsgGattcExcMtuCmd, GATTC_MTU_EXCH},
+        },
+        rwble_hl::error::{GapError, HlError, HlResult},
+    },
+    platform::core_modules::{
+        ke::task::ke_build_id,
+        rwip::{TASK_APP, TASK_GAPC, TASK_GATTC},
+    },
+};
+
+use super::{
+    device_config::DEVICE_CONFIG,
+    peer_info::{
+        check_connected, fail, time_out, PendingQueries, QueryCallback, QueryError, QueryResult,
+    },
+};
+
+/// Default ATT MTU
+pub const MTU_MIN: u16 = 23;
+/// Largest ATT MTU supported by the stack
+pub const MTU_MAX: u16 = 512;
+/// Shortest payload of a link layer data packet in bytes
+pub const TX_OCTETS_MIN: u16 = 27;
+/// Longest payload of a link layer data packet in bytes
+pub const TX_OCTETS_MAX: u16 = 251;
+/// Shortest transmission time of a link layer data packet in µs
+pub const TX_TIME_MIN: u16 = 328;
+/// Longest transmission time of a link layer data packet on the LE 1M PHY in µs
+pub const TX_TIME_MAX: u16 = 2120;
+
+#[derive(Debug)]
+pub enum LinkConfigError {
+    /// The MTU is out of range
+    InvalidMtu,
+    /// The MPS is out of range or larger than the MTU
+    InvalidMps,
+    /// The packet length is out of range
+    InvalidTxOctets,
+    /// The packet time is out of range
+    InvalidTxTime,
+    /// The request could not be issued
+    Query(QueryError),
+}
+
+impl From<QueryError> for LinkConfigError {
+    fn from(error: QueryError) -> Self {
+        Self::Query(error)
+    }
+}
+
+pub type LinkConfigResult<T = ()> = Result<T, LinkConfigError>;
+
+#[derive(Debug, Clone)]
+pub struct LinkConfiguration {
+    pub(super) max_mtu: u16,
+    pub(super) max_mps: u16,
+    pub(super) max_tx_octets: u16,
+    pub(super) max_tx_time: u16,
+    pub(super) auto_data_length: bool,
+}
+
+impl LinkConfiguration {
+    /// Default MTU with the largest data length, which is not negotiated automatically.
+    pub const fn new() -> Self {
+        Self {
+            max_mtu: MTU_MIN,
+            max_mps: 0,
+            max_tx_octets: TX_OCTETS_MAX,
+            max_tx_time: TX_TIME_MAX,
+            auto_data_length: false,
+        }
+    }
+
+    /// Largest MTU which is accepted in an MTU exchange
+    pub fn set_max_mtu(mut self, max_mtu: u16) -> Self {
+        self.max_mtu = max_mtu;
+        self
+    }
+
+    /// Largest L2CAP payload, 0 to use the MTU
+    pub fn set_max_mps(mut self, max_mps: u16) -> Self {
+        self.max_mps = max_mps;
+        self
+    }
+
+    /// Largest payload of a link layer data packet in bytes
+    pub fn set_max_tx_octets(mut self, max_tx_octets: u16) -> Self {
+        self.max_tx_octets = max_tx_octets;
+        self
+    }
+
+    /// Longest transmission time of a link layer data packet in µs
+    pub fn set_max_tx_time(mut self, max_tx_time: u16) -> Self {
+        self.max_tx_time = max_tx_time;
+        self
+    }
+
+    /// Requests the largest data length on every new connection, the peer has to support the
+    /// data length extension.
+    pub fn set_auto_data_length(mut self, auto_data_length: bool) -> Self {
+        self.auto_data_length = auto_data_length;
+        self
+    }
+
+    pub(super) fn validate(&self) -> LinkConfigResult {
+        if !(MTU_MIN..=MTU_MAX).contains(&self.max_mtu) {
+            return Err(LinkConfigError::InvalidMtu);
+        }
+
+        if self.max_mps != 0 && !(MTU_MIN..=self.max_mtu).contains(&self.max_mps) {
+            return Err(LinkConfigError::InvalidMps);
+        }
+
+        if !(TX_OCTETS_MIN..=TX_OCTETS_MAX).contains(&self.max_tx_octets) {
+            return Err(LinkConfigError::InvalidTxOctets);
+        }
+
+        if !(TX_TIME_MIN..=TX_TIME_MAX).contains(&self.max_tx_time) {
+            return Err(LinkConfigError::InvalidTxTime);
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for LinkConfiguration {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Data length of a connection, negotiated with the peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataLength {
+    pub max_tx_octets: u16,
+    /// µs
+    pub max_tx_time: u16,
+    pub max_rx_octets: u16,
+    /// µs
+    pub max_rx_time: u16,
+}
+
+#[link_section = "retention_mem_area0"]
+static mut MTU_EXCHANGE: PendingQueries<u16> = PendingQueries::new();
+
+#[link_section = "retention_mem_area0"]
+static mut DATA_LENGTH: PendingQueries<DataLength> = PendingQueries::new();
+
+fn app_easy_gatt_exchange_mtu_create_msg(conidx: u8) -> KeMsgGattcExcMtuCmd {
+    let mut cmd = KeMsgGattcExcMtuCmd::new(TASK_APP as u16, ke_build_id(TASK_GATTC as u8, conidx));
+
+    let msg = cmd.fields();
+
+    msg.operation = GATTC_MTU_EXCH as u8;
+    msg.seq_num = 0;
+
+    cmd
+}
+
+fn app_easy_gap_set_data_packet_length_create_msg(
+    conidx: u8,
+    tx_octets: u16,
+    tx_time: u16,
+) -> KeMsgGapcSetLePktSizeCmd {
+    let mut cmd =
+        KeMsgGapcSetLePktSizeCmd::new(TASK_APP as u16, ke_build_id(TASK_GAPC as u8, conidx));
+
+    let msg = cmd.fields();
+
+    msg.operation = GAPC_SET_LE_PKT_SIZE as u8;
+    msg.tx_octets = tx_octets;
+    msg.tx_time = tx_time;
+
+    cmd
+}
+
+/// Exchanges the MTU with the peer of connection `conidx`, `callback` is called with the
+/// negotiated MTU.
+pub fn app_easy_gatt_exchange_mtu(conidx: u8, callback: QueryCallback<u16>) -> QueryResult {
+    check_connected(conidx)?;
+
+    unsafe {
+        MTU_EXCHANGE.push(conidx, callback, |id| {
+            time_out(MTU_EXCHANGE.take_expired(id))
+        })
+    }?;
+
+    app_easy_gatt_exchange_mtu_create_msg(conidx).send();
+
+    Ok(())
+}
+
+/// Requests the data length `tx_octets` and `tx_time` on connection `conidx`, `callback` is
+/// called with the data length negotiated with the peer.
+pub fn app_easy_gap_set_data_packet_length(
+    conidx: u8,
+    tx_octets: u16,
+    tx_time: u16,
+    callback: QueryCallback<DataLength>,
+) -> LinkConfigResult {
+    if !(TX_OCTETS_MIN..=TX_OCTETS_MAX).contains(&tx_octets) {
+        return Err(LinkConfigError::InvalidTxOctets);
+    }
+
+    if !(TX_TIME_MIN..=TX_TIME_MAX).contains(&tx_time) {
+        return Err(LinkConfigError::InvalidTxTime);
+    }
+
+    check_connected(conidx)?;
+
+    unsafe {
+        DATA_LENGTH.push(conidx, callback, |id| {
+            time_out(DATA_LENGTH.take_expired(id))
+        })
+    }?;
+
+    app_easy_gap_set_data_packet_length_create_msg(conidx, tx_octets, tx_time).send();
+
+    Ok(())
+}
+
+pub(crate) fn on_connection(conidx: u8) {
+    let link = unsafe { &DEVICE_CONFIG.link };
+
+    if link.auto_data_length {
+        app_easy_gap_set_data_packet_length_create_msg(
+            conidx,
+            link.max_tx_octets,
+            link.max_tx_time,
+        )
+        .send();
+    }
+}
+
+/// The indication is also received for exchanges initiated by the peer.
+pub(crate) fn on_mtu_changed_ind(conidx: u8, param: &GattcMtuChangedInd) {
+    if let Some(callback) = unsafe { MTU_EXCHANGE.take(conidx) } {
+        callback(Ok(param.mtu));
+    }
+}
+
+/// The indication is also received for changes initiated by the peer.
+pub(crate) fn on_data_length_change(conidx: u8, param: &GapcLePktSizeInd) {
+    if let Some(callback) = unsafe { DATA_LENGTH.take(conidx) } {
+        callback(Ok(DataLength {
+            max_tx_octets: param.max_tx_octets,
+            max_tx_time: param.max_tx_time,
+            max_rx_octets: param.max_rx_octets,
+            max_rx_time: param.max_rx_time,
+        }));
+    }
+}
+
+/// Fails a rejected data length request, the indication is not received in that case.
+pub(crate) fn on_gapc_cmp_evt(conidx: u8, operation: u8, result: HlResult) {
+    if operation as u32 == GAPC_SET_LE_PKT_SIZE {
+        unsafe { fail(&mut DATA_LENGTH, conidx, result) };
+    }
+}
+
+pub(crate) fn on_gattc_cmp_evt(conidx: u8, operation: u8, result: HlResult) {
+    if operation as u32 == GATTC_MTU_EXCH {
+        unsafe { fail(&mut MTU_EXCHANGE, conidx, result) };
+    }
+}
+
+/// Fails all requests with [`GapError::Canceled`] before the stack is reset.
+pub(crate) fn on_reset() {
+    let error = HlError::Gap(GapError::Canceled);
+
+    unsafe {
+        MTU_EXCHANGE
+            .drain()
+            .into_iter()
+            .for_each(|callback| callback(Err(error)));
+        DATA_LENGTH
+            .drain()
+            .into_iter()
+            .for_each(|callback| callback(Err(error)));
+    }
+}
+
+pub(crate) fn on_disconnect(conidx: u8) {
+    let error = HlError::Gap(GapError::Disconnected);
+
+    unsafe {
+        MTU_EXCHANGE
+            .take_all(conidx)
+            .into_iter()
+            .for_each(|callback| callback(Err(error)));
+        DATA_LENGTH
+            .take_all(conidx)
+            .into_iter()
+            .for_each(|callback| callback(Err(error)));
+    }
+}
@@ -8,42 +8,62 @@ use crate::{
             app_default_handler, app_state, AppDeviceInfo, AppDeviceName, APP_CONNECTABLE,
             APP_DISABLED, APP_IDX_MAX, APP_STATE_MAX,
         },
-        zero_app_prf_srv_sec, AdvertiseConfiguration, AppCallbacks, AppPrfSrvSec,
-        GapmConfiguration, PrfFuncCallbacks, PRFS_TASK_ID_MAX,
+        app_task::APP_CONNECTED,
+        zero_app_prf_srv_sec, AdvertiseConfiguration, AppCallbacks, AppPrfSrvSec, PrfFuncCallbacks,
+        PRFS_TASK_ID_MAX,
     },
     ble_stack::{
-        controller::llm::llm_le_env,
+        controller::llm::{llm_le_env, LLM_ADV_INTERVAL_MAX, LLM_ADV_INTERVAL_MIN},
         host::gap::{
+            gapc::task::{GapcParamUpdatedInd, KeMsgGapcParamUpdateCmd, GAPC_UPDATE_PARAMS},
             gapm::task::{
-                KeMsgGapmSetDevConfigCmd, KeMsgGapmStartAdvertiseCmd, GAPM_ADV_UNDIRECT,
-                GAPM_MASK_ATT_SVC_CHG_EN, GAPM_SET_DEV_CONFIG,
+                GapmAdvHost, KeMsgGapmCancelCmd, KeMsgGapmSetDevConfigCmd,
+                KeMsgGapmStartAdvertiseCmd, KeMsgGapmUpdateAdvertiseDataCmd, GAPM_ADV_DIRECT,
+                GAPM_ADV_DIRECT_LDC, GAPM_ADV_NON_CONN, GAPM_ADV_UNDIRECT, GAPM_CANCEL,
+                GAPM_SET_DEV_CONFIG, GAPM_UPDATE_ADVERTISE_DATA,
             },
-            GAP_AD_TYPE_COMPLETE_NAME, GAP_MAX_NAME_SIZE, GAP_NON_DISCOVERABLE,
-            GAP_ROLE_PERIPHERAL,
+            GapBDAddr, GAP_BROADCASTER_MODE, GAP_MAX_NAME_SIZE, GAP_NON_DISCOVERABLE, GAP_ROLE_ALL,
+            GAP_ROLE_BROADCASTER, GAP_ROLE_CENTRAL, GAP_ROLE_OBSERVER, GAP_ROLE_PERIPHERAL,
         },
+        rwble_hl::error::{GapError, HlError, HlResult},
     },
     platform::core_modules::{
         common::{
             co_min, BDAddr, ADV_ALLOW_SCAN_ANY_CON_WLST, ADV_ALL_CHNLS_EN, ADV_CHNL_37_EN,
             ADV_CHNL_38_EN, ADV_CHNL_39_EN, ADV_DATA_LEN, KEY_LEN, SCAN_RSP_DATA_LEN,
         },
-        ke::task::{ke_state_set, ke_task_create, KeTaskDesc},
-        rwip::{KeApiId, TASK_APP, TASK_GAPM, TASK_ID_DISS, TASK_ID_INVALID},
+        ke::task::{ke_build_id, ke_state_get, ke_state_set, ke_task_create, KeTaskDesc},
+        rwip::{KeApiId, TASK_APP, TASK_GAPC, TASK_GAPM, TASK_ID_DISS, TASK_ID_INVALID},
     },
 };
 
 #[cfg(feature = "profile_custom_server")]
 use crate::app_modules::app_custs::CustPrfFuncCallbacks;
 
+use alloc::boxed::Box;
+
+#[cfg(not(feature = "exclude_dlg_timer"))]
+use crate::app_modules::timer::{AppTimer, AppTimerResult};
+
 #[cfg(feature = "address_mode_public")]
 use crate::app_modules::APP_CFG_ADDR_PUB;
 
 #[cfg(feature = "address_mode_static")]
 use crate::app_modules::APP_CFG_ADDR_STATIC;
 
-mod advertise;
+pub mod ad_parser;
+pub mod ad_types;
+pub mod advertise;
+pub mod beacon;
+pub mod central;
+pub mod connections;
+pub mod device_config;
+pub mod link;
+pub mod peer_info;
+pub mod scan;
 
 use advertise::*;
+use device_config::DEVICE_CONFIG;
 
 #[link_section = "retention_mem_area0"]
 #[no_mangle]
@@ -223,50 +243,117 @@ const USER_PRF_FUNCS: [PrfFuncCallbacks; 1] = [PrfFuncCallbacks {
     enable_func: None,
 }];
 
-static mut USER_GAPM_CONF: GapmConfiguration = GapmConfiguration {
-    role: GAP_ROLE_PERIPHERAL,
-    max_mtu: 23,
-    #[cfg(feature = "address_mode_public")]
-    addr_type: app_cfg_addr_type(APP_CFG_ADDR_PUB),
-    #[cfg(feature = "address_mode_static")]
-    addr_type: app_cfg_addr_type(APP_CFG_ADDR_STATIC),
-    addr: [0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
-    renew_dur: 15000,
-    irk: [
-        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
-        0x0f,
-    ],
-    att_cfg: GAPM_MASK_ATT_SVC_CHG_EN as u8,
-    gap_start_hdl: 0,
-    gatt_start_hdl: 0,
-    max_mps: 0,
-    max_txoctets: 251,
-    max_txtime: 2120,
-};
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GapRole {
+    /// Scanning only
+    Observer,
+    /// Advertising only
+    Broadcaster,
+    /// Scanning and initiating connections
+    Central,
+    /// Advertising and accepting connections
+    Peripheral,
+    /// Central and peripheral at the same time
+    All,
+}
+
+impl From<GapRole> for u8 {
+    fn from(role: GapRole) -> Self {
+        (match role {
+            GapRole::Observer => GAP_ROLE_OBSERVER,
+            GapRole::Broadcaster => GAP_ROLE_BROADCASTER,
+            GapRole::Central => GAP_ROLE_CENTRAL,
+            GapRole::Peripheral => GAP_ROLE_PERIPHERAL,
+            GapRole::All => GAP_ROLE_ALL,
+        }) as u8
+    }
+}
+
+/// Sets the role of the device configuration.
+pub fn set_gap_role(role: GapRole) {
+    unsafe {
+        DEVICE_CONFIG.role = role;
+    }
+}
 
 #[cfg(feature = "address_mode_static")]
 static mut APP_RANDOM_ADDR: BDAddr = BDAddr { addr: [0; 6] };
 
-configure_user_adv_data!(
-    {ADV_TYPE_COMPLETE_LIST_16BIT_SERVICE_IDS, 0x6b, 0xfd},
-    {ADV_TYPE_MANUFACTURER_SPECIFIC_DATA, 0x98, 0x05, 0x01, 0x90, 0x01, 0x02, 0x22}
-);
+#[link_section = "retention_mem_area0"]
+static mut USER_ADVERTISE_DATA: AdvData = AdvData::new();
+
+#[link_section = "retention_mem_area0"]
+static mut USER_ADVERTISE_SCAN_RESPONSE_DATA: AdvData = AdvData::new_scan_response();
 
-configure_user_scan_response_data!();
+/// Sets the advertising data used by subsequently started advertising operations.
+pub fn set_user_advertise_data(adv_data: AdvData) {
+    unsafe {
+        USER_ADVERTISE_DATA = adv_data;
+    }
+}
 
-fn update_device_info() {
+/// Sets the scan response data used by subsequently started advertising operations.
+pub fn set_user_scan_response_data(scan_rsp_data: AdvData) {
     unsafe {
-        let cropped_len = if USER_DEVICE_NAME.len() <= GAP_MAX_NAME_SIZE as usize {
-            USER_DEVICE_NAME.len()
-        } else {
-            GAP_MAX_NAME_SIZE as usize
-        };
+        USER_ADVERTISE_SCAN_RESPONSE_DATA = scan_rsp_data;
+    }
+}
 
-        device_info.dev_name.length = cropped_len as u8;
+/// Where the device name is placed when advertising is started or its data is updated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceNamePlacement {
+    /// Advertising data only
+    AdvData,
+    /// Scan response data only
+    ScanResponse,
+    /// Advertising data if the complete name fits, else scan response data
+    Auto,
+}
+
+#[link_section = "retention_mem_area0"]
+static mut DEVICE_NAME_PLACEMENT: DeviceNamePlacement = DeviceNamePlacement::Auto;
+
+pub fn set_device_name_placement(placement: DeviceNamePlacement) {
+    unsafe {
+        DEVICE_NAME_PLACEMENT = placement;
+    }
+}
 
-        device_info.dev_name.name[..cropped_len]
-            .copy_from_slice((&USER_DEVICE_NAME[..cropped_len]).as_bytes());
+/// Longest prefix of `name` of at most `max_len` bytes which does not split a character
+fn crop_name(name: &str, max_len: usize) -> &str {
+    let mut len = name.len().min(max_len);
 
+    while !name.is_char_boundary(len) {
+        len -= 1;
+    }
+
+    &name[..len]
+}
+
+/// Sets the device name which is advertised and reported to peers.
+///
+/// The name is cropped to `GAP_MAX_NAME_SIZE` bytes. Running advertising operations are not
+/// updated.
+pub fn app_easy_gap_set_device_name(name: &str) {
+    let name = crop_name(name, GAP_MAX_NAME_SIZE as usize);
+
+    unsafe {
+        device_info.dev_name.length = name.len() as u8;
+        device_info.dev_name.name[..name.len()].copy_from_slice(name.as_bytes());
+    }
+}
+
+/// The current device name, initially `USER_DEVICE_NAME`
+pub fn app_easy_gap_device_name() -> &'static str {
+    let name = unsafe { &device_info.dev_name.name[..device_info.dev_name.length as usize] };
+
+    core::str::from_utf8(name).unwrap_or_default()
+}
+
+fn update_device_info() {
+    app_easy_gap_set_device_name(unsafe { USER_DEVICE_NAME });
+
+    unsafe {
         device_info.appearance = 0x0200; // Tag appearance
     }
 }
@@ -330,12 +417,161 @@ pub extern "C" fn app_prf_enable(conidx: u8) {
     callbacks.for_each(|cb| unsafe { cb(conidx) });
 }
 
-fn app_easy_gap_undirected_advertise_start_create_msg() -> KeMsgGapmStartAdvertiseCmd {
-    let user_advertise_data = unsafe { &USER_ADVERTISE_DATA };
+/// Space the stack reserves for the flags AD structure.
+///
+/// The stack adds the flags in every mode but `GAP_BROADCASTER_MODE`, also in
+/// `GAP_NON_DISCOVERABLE` which undirected advertising is forced into by a white list filter.
+#[inline]
+fn app_easy_gap_adv_flags_len(mode: u8) -> u16 {
+    if mode == GAP_BROADCASTER_MODE as u8 {
+        0
+    } else {
+        3
+    }
+}
+
+/// Space of the advertising data reserved by the most recently started advertising operation
+#[link_section = "retention_mem_area0"]
+static mut ADV_FLAGS_LEN: Option<u16> = None;
+
+/// Copies the user advertising and scan response data and places the device name, `host.mode`
+/// has to be set before.
+fn app_easy_gap_fill_adv_host_data(host: &mut GapmAdvHost) {
+    let flags_len = app_easy_gap_adv_flags_len(host.mode);
+
+    unsafe {
+        ADV_FLAGS_LEN = Some(flags_len);
+    }
+
+    let user_advertise_data = unsafe { USER_ADVERTISE_DATA.as_bytes() };
+    let user_scan_response_data = unsafe { USER_ADVERTISE_SCAN_RESPONSE_DATA.as_bytes() };
+
+    host.adv_data_len = user_advertise_data.len() as u8;
+    host.adv_data[..user_advertise_data.len()].copy_from_slice(user_advertise_data);
+
+    host.scan_rsp_data_len = user_scan_response_data.len() as u8;
+    host.scan_rsp_data[..user_scan_response_data.len()].copy_from_slice(user_scan_response_data);
+
+    let adv_data_max = (ADV_DATA_LEN as u16 - flags_len) as usize;
+
+    app_easy_gap_append_device_name(
+        &mut host.adv_data[..adv_data_max],
+        &mut host.adv_data_len,
+        &mut host.scan_rsp_data[..SCAN_RSP_DATA_LEN as usize],
+        &mut host.scan_rsp_data_len,
+    );
+}
+
+/// Appends the device name to the advertising or scan response data according to the
+/// [`DeviceNamePlacement`].
+///
+/// The complete name is preferred, if it fits into neither of the allowed places it is truncated
+/// to a shortened name in the place with the most space left. The name is dropped if not even a
+/// single character fits.
+fn app_easy_gap_append_device_name(
+    adv_data: &mut [u8],
+    adv_data_len: &mut u8,
+    scan_rsp_data: &mut [u8],
+    scan_rsp_data_len: &mut u8,
+) {
+    let device_name = app_easy_gap_device_name();
+
+    if device_name.is_empty() {
+        return;
+    }
+
+    let mut places = match unsafe { DEVICE_NAME_PLACEMENT } {
+        DeviceNamePlacement::AdvData => [Some((adv_data, adv_data_len)), None],
+        DeviceNamePlacement::ScanResponse => [Some((scan_rsp_data, scan_rsp_data_len)), None],
+        DeviceNamePlacement::Auto => [
+            Some((adv_data, adv_data_len)),
+            Some((scan_rsp_data, scan_rsp_data_len)),
+        ],
+    };
+
+    let space_left =
+        |(data, data_len): &(&mut [u8], &mut u8)| data.len().saturating_sub(**data_len as usize);
+
+    let complete = places.iter().position(
+        |place| matches!(place, Some(place) if space_left(place) >= 2 + device_name.len()),
+    );
+
+    let (index, ad_type, name) = match complete {
+        Some(index) => (index, ADV_TYPE_COMPLETE_LOCAL_NAME, device_name),
+        None => {
+            // The first place wins if both have the same space left
+            let Some((index, space)) = places
+                .iter()
+                .enumerate()
+                .filter_map(|(index, place)| Some((index, space_left(place.as_ref()?))))
+                .rev()
+                .max_by_key(|(_, space)| *space)
+            else {
+                return;
+            };
+
+            let name = crop_name(device_name, space.saturating_sub(2));
+            if name.is_empty() {
+                return;
+            }
+
+            (index, ADV_TYPE_SHORTENED_LOCAL_NAME, name)
+        }
+    };
+
+    let (data, data_len) = places[index].take().unwrap();
+
+    let mut offset = *data_len as usize;
+
+    data[offset] = 1 + name.len() as u8;
+    offset += 1;
+    data[offset] = ad_type;
+    offset += 1;
+
+    data[offset..(offset + name.len())].copy_from_slice(name.as_bytes());
 
-    assert!(user_advertise_data.len() <= ADV_DATA_LEN as usize);
-    assert!(USER_ADVERTISE_SCAN_RESPONSE_DATA.len() <= SCAN_RSP_DATA_LEN as usize);
+    *data_len = (offset + name.len()) as u8;
+}
+
+fn app_easy_gap_update_advertise_data_create_msg(
+    adv_data: &AdvData,
+    scan_rsp_data: &AdvData,
+) -> AdvDataResult<KeMsgGapmUpdateAdvertiseDataCmd> {
+    let mut cmd = KeMsgGapmUpdateAdvertiseDataCmd::new(TASK_APP as u16, TASK_GAPM as u16);
+
+    let msg = cmd.fields();
+
+    // The stack adds the flags to the updated data as it does when advertising is started
+    let flags_len = unsafe { ADV_FLAGS_LEN }
+        .unwrap_or_else(|| app_easy_gap_adv_flags_len(unsafe { USER_ADV_CONF.mode }));
+    let adv_data_max = msg
+        .adv_data
+        .len()
+        .min((ADV_DATA_LEN as u16 - flags_len) as usize);
+
+    if adv_data.len() > adv_data_max || scan_rsp_data.len() > msg.scan_rsp_data.len() {
+        return Err(AdvDataError::Overflow);
+    }
 
+    msg.operation = GAPM_UPDATE_ADVERTISE_DATA as u8;
+
+    msg.adv_data_len = adv_data.len() as u8;
+    msg.adv_data[..adv_data.len()].copy_from_slice(adv_data.as_bytes());
+
+    msg.scan_rsp_data_len = scan_rsp_data.len() as u8;
+    msg.scan_rsp_data[..scan_rsp_data.len()].copy_from_slice(scan_rsp_data.as_bytes());
+
+    app_easy_gap_append_device_name(
+        &mut msg.adv_data[..adv_data_max],
+        &mut msg.adv_data_len,
+        &mut msg.scan_rsp_data[..SCAN_RSP_DATA_LEN as usize],
+        &mut msg.scan_rsp_data_len,
+    );
+
+    Ok(cmd)
+}
+
+fn app_easy_gap_undirected_advertise_start_create_msg() -> KeMsgGapmStartAdvertiseCmd {
     let mut cmd = KeMsgGapmStartAdvertiseCmd::new(TASK_APP as u16, TASK_GAPM as u16);
 
     let msg = cmd.fields();
@@ -356,13 +592,6 @@ fn app_easy_gap_undirected_advertise_start_create_msg() -> KeMsgGapmStartAdverti
 
     let host = unsafe { &mut msg.info.host };
 
-    host.adv_data_len = user_advertise_data.len() as u8;
-    host.adv_data[..user_advertise_data.len()].copy_from_slice(user_advertise_data);
-
-    host.scan_rsp_data_len = USER_ADVERTISE_SCAN_RESPONSE_DATA.len() as u8;
-    host.scan_rsp_data[..USER_ADVERTISE_SCAN_RESPONSE_DATA.len()]
-        .copy_from_slice(&USER_ADVERTISE_SCAN_RESPONSE_DATA);
-
     // #if (USER_CFG_ADDRESS_MODE == APP_CFG_CNTL_PRIV_RPA_RAND)
     //         // Local Address has been added to RAL. Use this entry to advertise with RPA
     //         memcpy(cmd->info.host.peer_info.addr.addr, &(gapm_env.addr), BD_ADDR_LEN * sizeof(uint8_t));
@@ -389,37 +618,61 @@ fn app_easy_gap_undirected_advertise_start_create_msg() -> KeMsgGapmStartAdverti
     //         }
     // #endif
 
-    let device_name = unsafe { USER_DEVICE_NAME };
+    app_easy_gap_fill_adv_host_data(host);
 
-    if device_name.len() > 0 {
-        let total_adv_space = 3 + host.adv_data_len as u16 + 2 + device_name.len() as u16;
-        let total_scan_space = host.scan_rsp_data_len as u16 + 2 + device_name.len() as u16;
+    cmd
+}
 
-        if total_adv_space <= ADV_DATA_LEN as u16 {
-            let mut offset = host.adv_data_len as usize;
+fn app_easy_gap_non_connectable_advertise_start_create_msg() -> KeMsgGapmStartAdvertiseCmd {
+    let mut cmd = KeMsgGapmStartAdvertiseCmd::new(TASK_APP as u16, TASK_GAPM as u16);
 
-            host.adv_data[offset] = 1 + device_name.len() as u8;
-            offset += 1;
-            host.adv_data[offset] = GAP_AD_TYPE_COMPLETE_NAME as u8;
-            offset += 1;
+    let msg = cmd.fields();
+
+    let user_adv_conf = unsafe { &USER_ADV_CONF };
+
+    msg.op.code = GAPM_ADV_NON_CONN as u8;
+    msg.op.addr_src = user_adv_conf.addr_src;
+    msg.intv_min = user_adv_conf.intv_min;
+    msg.intv_max = user_adv_conf.intv_max;
+    msg.channel_map = user_adv_conf.channel_map;
+    msg.info.host.mode = user_adv_conf.mode;
+    msg.info.host.adv_filt_policy = user_adv_conf.adv_filt_policy;
 
-            host.adv_data[offset..(offset + device_name.len())]
-                .copy_from_slice(device_name.as_bytes());
+    let host = unsafe { &mut msg.info.host };
 
-            host.adv_data_len = (offset + device_name.len()) as u8;
-        } else if total_scan_space <= SCAN_RSP_DATA_LEN as u16 {
-            let mut offset = host.scan_rsp_data_len as usize;
+    app_easy_gap_fill_adv_host_data(host);
 
-            host.scan_rsp_data[offset] = 1 + device_name.len() as u8;
-            offset += 1;
-            host.scan_rsp_data[offset] = GAP_AD_TYPE_COMPLETE_NAME as u8;
-            offset += 1;
+    cmd
+}
 
-            host.scan_rsp_data[offset..(offset + device_name.len())]
-                .copy_from_slice(device_name.as_bytes());
+fn app_easy_gap_directed_advertise_start_create_msg(
+    peer: &GapBDAddr,
+    ldc_enable: bool,
+) -> KeMsgGapmStartAdvertiseCmd {
+    let mut cmd = KeMsgGapmStartAdvertiseCmd::new(TASK_APP as u16, TASK_GAPM as u16);
 
-            host.scan_rsp_data_len = (offset + device_name.len()) as u8;
-        }
+    let msg = cmd.fields();
+
+    let user_adv_conf = unsafe { &USER_ADV_CONF };
+
+    if ldc_enable {
+        msg.op.code = GAPM_ADV_DIRECT_LDC as u8;
+        msg.intv_min = user_adv_conf.intv_min;
+        msg.intv_max = user_adv_conf.intv_max;
+    } else {
+        // High duty cycle directed advertising does not use the advertising interval
+        msg.op.code = GAPM_ADV_DIRECT as u8;
+        msg.intv_min = LLM_ADV_INTERVAL_MIN as u16;
+        msg.intv_max = LLM_ADV_INTERVAL_MAX as u16;
+    }
+
+    msg.op.addr_src = user_adv_conf.addr_src;
+    msg.channel_map = user_adv_conf.channel_map;
+    msg.info.direct = *peer;
+
+    // Directed advertising carries no advertising data, there is no room for updates
+    unsafe {
+        ADV_FLAGS_LEN = Some(ADV_DATA_LEN as u16);
     }
 
     cmd
@@ -432,11 +685,19 @@ fn app_easy_gap_dev_config_create_msg() -> KeMsgGapmSetDevConfigCmd {
 
     msg.operation = GAPM_SET_DEV_CONFIG as u8;
 
-    let user_gapm_conf = unsafe { &USER_GAPM_CONF };
+    let config = unsafe { &DEVICE_CONFIG };
+
+    msg.role = u8::from(config.role);
+    msg.max_mtu = config.link.max_mtu;
 
-    msg.role = user_gapm_conf.role as u8;
-    msg.max_mtu = user_gapm_conf.max_mtu;
-    msg.addr_type = user_gapm_conf.addr_type;
+    #[cfg(feature = "address_mode_public")]
+    {
+        msg.addr_type = app_cfg_addr_type(APP_CFG_ADDR_PUB);
+    }
+    #[cfg(feature = "address_mode_static")]
+    {
+        msg.addr_type = app_cfg_addr_type(APP_CFG_ADDR_STATIC);
+    }
 
     #[cfg(any(feature = "address_mode_public", feature = "address_mode_static"))]
     {
@@ -445,16 +706,16 @@ fn app_easy_gap_dev_config_create_msg() -> KeMsgGapmSetDevConfigCmd {
     }
     #[cfg(not(any(feature = "address_mode_public", feature = "address_mode_static")))]
     {
-        msg.renew_dur = user_gapm_conf.renew_dur;
-        msg.irk.key.copy_from_slice(&user_gapm_conf.irk);
+        msg.renew_dur = config.renew_dur;
+        msg.irk.key.copy_from_slice(&config.irk);
     }
 
     #[cfg(feature = "address_mode_static")]
     {
-        // let tmp_addr = [0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
-
-        // Check whether the user-defined Random Static address is null.
-        if user_gapm_conf.addr.iter().cloned().sum::<u8>() == 0 {
+        if let Some(static_addr) = &config.static_addr {
+            // Validated by set_device_configuration
+            msg.addr = *static_addr;
+        } else {
             let mut bd_addr = BDAddr { addr: [0; 6] };
             if let Some(app_on_generate_static_random_addr) =
                 unsafe { user_app_callbacks.app_on_generate_static_random_addr }
@@ -462,40 +723,20 @@ fn app_easy_gap_dev_config_create_msg() -> KeMsgGapmSetDevConfigCmd {
                 unsafe {
                     app_on_generate_static_random_addr(&mut bd_addr);
                 }
-                unsafe {
-                    msg.addr.addr.copy_from_slice(&bd_addr.addr);
-                }
+                msg.addr.addr.copy_from_slice(&bd_addr.addr);
             } else {
                 panic!("With static address mode, you either need to defined a valid static address or the callback app_on_generate_static_random_addr!");
             }
-        } else {
-            todo!();
         }
-        //
-        //         if (memcmp(USER_GAPM_CONF.addr, &co_null_bdaddr, BD_ADDR_LEN) == 0)
-        //         {
-        //             CALLBACK_ARGS_1(user_app_callbacks.app_on_generate_static_random_addr, &app_random_addr)
-        //             memcpy(msg.addr.addr, app_random_addr.addr, BD_ADDR_LEN * sizeof(uint8_t));
-        //         }
-        //         else
-        //         {
-        //             // All the (BD_ADDR_LEN - 1) * sizeof(uint8_t) least significant
-        //             // bits of the user-defined Random Static address shall NOT be equal to 1.
-        //             ASSERT_ERROR(memcmp(USER_GAPM_CONF.addr, tmp_addr, BD_ADDR_LEN - 1) != 0);
-        //             // The two most significant bits of the user-defined Random Static
-        //             // address shall be equal to 1.
-        //             ASSERT_ERROR((USER_GAPM_CONF.addr[BD_ADDR_LEN - 1] & GAP_STATIC_ADDR) == GAP_STATIC_ADDR)
-        //             memcpy(msg.addr.addr, USER_GAPM_CONF.addr, BD_ADDR_LEN * sizeof(uint8_t));
-        //         }
-    }
-
-    msg.att_cfg = user_gapm_conf.att_cfg;
-    msg.gap_start_hdl = user_gapm_conf.gap_start_hdl;
-    msg.gatt_start_hdl = user_gapm_conf.gatt_start_hdl;
-    msg.max_mps = user_gapm_conf.max_mps;
+    }
+
+    msg.att_cfg = config.att_cfg_bits();
+    msg.gap_start_hdl = config.gap_start_hdl;
+    msg.gatt_start_hdl = config.gatt_start_hdl;
+    msg.max_mps = config.link.max_mps;
     unsafe {
-        msg.max_txoctets = co_min(user_gapm_conf.max_txoctets, llm_le_env.supportedMaxTxOctets);
-        msg.max_txtime = co_min(user_gapm_conf.max_txtime, llm_le_env.supportedMaxTxTime);
+        msg.max_txoctets = co_min(config.link.max_tx_octets, llm_le_env.supportedMaxTxOctets);
+        msg.max_txtime = co_min(config.link.max_tx_time, llm_le_env.supportedMaxTxTime);
     }
 
     cmd
@@ -517,249 +758,302 @@ pub fn app_easy_gap_undirected_advertise_start() {
     ke_state_set(TASK_APP as u16, APP_CONNECTABLE as u8);
 }
 
+/// Starts non-connectable advertising, which is also possible while connected.
+pub fn app_easy_gap_non_connectable_advertise_start() {
+    app_easy_gap_non_connectable_advertise_start_create_msg().send();
+
+    if ke_state_get(TASK_APP as u16) != APP_CONNECTED as u8 {
+        ke_state_set(TASK_APP as u16, APP_CONNECTABLE as u8);
+    }
+}
+
+/// Starts directed advertising to the peer configured in `USER_ADV_CONF`.
+///
+/// Low duty cycle directed advertising (`ldc_enable`) uses the configured advertising interval.
+pub fn app_easy_gap_directed_advertise_start(ldc_enable: bool) {
+    let user_adv_conf = unsafe { &USER_ADV_CONF };
+
+    let peer = GapBDAddr {
+        addr: BDAddr {
+            addr: user_adv_conf.peer_addr,
+        },
+        addr_type: user_adv_conf.peer_addr_type,
+    };
+
+    app_easy_gap_directed_advertise_start_to(&peer, ldc_enable);
+}
+
+/// Starts directed advertising to `peer`, e.g. a bonded device.
+pub fn app_easy_gap_directed_advertise_start_to(peer: &GapBDAddr, ldc_enable: bool) {
+    app_easy_gap_directed_advertise_start_create_msg(peer, ldc_enable).send();
+    ke_state_set(TASK_APP as u16, APP_CONNECTABLE as u8);
+}
+
 #[inline]
 pub fn app_easy_gap_advertise_stop() {
     unsafe { crate::bindings::app_easy_gap_advertise_stop() }
 }
 
+#[cfg(not(feature = "exclude_dlg_timer"))]
+pub type AdvTimeoutCallback = Box<dyn FnOnce()>;
+
+#[cfg(not(feature = "exclude_dlg_timer"))]
+#[link_section = "retention_mem_area0"]
+static mut ADV_TIMER: Option<AppTimer> = None;
+
+/// Starts undirected advertising which is stopped after `delay` timer units.
+///
+/// `callback` is called after advertising has been stopped due to the timeout. The timeout is
+/// canceled if a connection is established before. Advertising is not started if the timer cannot
+/// be created.
+#[cfg(not(feature = "exclude_dlg_timer"))]
+pub fn app_easy_gap_undirected_advertise_with_timeout_start(
+    delay: u32,
+    callback: Option<AdvTimeoutCallback>,
+) -> AppTimerResult {
+    // Stop the current running timer
+    app_easy_gap_advertise_with_timeout_stop();
+
+    let timer = AppTimer::new(
+        delay,
+        Box::new(move || {
+            // The timer has expired and released its slot
+            unsafe {
+                ADV_TIMER = None;
+            }
+
+            app_easy_gap_advertise_stop();
+
+            if let Some(callback) = callback {
+                callback();
+            }
+        }),
+    )?;
+
+    unsafe {
+        ADV_TIMER = Some(timer);
+    }
+
+    app_easy_gap_undirected_advertise_start();
+
+    Ok(())
+}
+
+/// Updates the advertising and scan response data of the running advertising operation.
+///
+/// The device name is placed the same way as when advertising is started. The user advertising
+/// data used by subsequently started advertising operations is not changed.
+pub fn app_easy_gap_update_advertise_data(
+    adv_data: &AdvData,
+    scan_rsp_data: &AdvData,
+) -> AdvDataResult {
+    app_easy_gap_update_advertise_data_create_msg(adv_data, scan_rsp_data)?.send();
+
+    Ok(())
+}
+
+/// Cancels the timeout of advertising started with a timeout, advertising is not stopped.
+#[cfg(not(feature = "exclude_dlg_timer"))]
+pub fn app_easy_gap_advertise_with_timeout_stop() {
+    if let Some(timer) = unsafe { ADV_TIMER.take() } {
+        let _ = timer.cancel();
+    }
+}
+
+/// Cancels the ongoing air operation of the GAP manager, i.e. advertising, scanning or
+/// establishing a connection.
+pub fn app_easy_gap_cancel() {
+    let mut cmd = KeMsgGapmCancelCmd::new(TASK_APP as u16, TASK_GAPM as u16);
+
+    cmd.fields().operation = GAPM_CANCEL as u8;
+
+    cmd.send();
+}
+
 #[no_mangle]
 pub extern "C" fn app_easy_gap_dev_configure() {
     app_easy_gap_dev_config_create_msg().send();
 }
 
-// fn app_easy_gap_non_connectable_advertise_start_create_msg() -> KeMsgGapmStartAdvertiseCmd {
-//     assert!(USER_ADVERTISE_DATA.len() <= ADV_DATA_LEN as usize);
-//     assert!(USER_ADVERTISE_SCAN_RESPONSE_DATA.len() <= SCAN_RSP_DATA_LEN as usize);
-//     let mut cmd = KeMsgGapmStartAdvertiseCmd::new(TASK_APP as u16, TASK_GAPM as u16);
-//     cmd.fields().op.code = GAPM_ADV_NON_CONN as u8;
-//     unsafe {
-//         cmd.fields().op.addr_src = USER_ADV_CONF.addr_src;
-//         cmd.fields().intv_min = USER_ADV_CONF.intv_min;
-//         cmd.fields().intv_max = USER_ADV_CONF.intv_max;
-//         cmd.fields().channel_map = USER_ADV_CONF.channel_map;
-//         cmd.fields().info.host.mode = USER_ADV_CONF.mode;
-//         cmd.fields().info.host.adv_filt_policy = USER_ADV_CONF.adv_filt_policy;
-//     }
-//     let host = unsafe { &mut cmd.fields().info.host };
-//     let adv_data_len = &mut host.adv_data_len;
-//     let adv_data = &mut host.adv_data;
-//     *adv_data_len = USER_ADVERTISE_DATA.len() as u8;
-//     adv_data.clone_from_slice(&USER_ADVERTISE_DATA);
-//     let scan_rsp_data_len = &mut host.scan_rsp_data_len;
-//     let scan_rsp_data = &mut host.scan_rsp_data;
-//     *scan_rsp_data_len = USER_ADVERTISE_SCAN_RESPONSE_DATA.len() as u8;
-//     scan_rsp_data.clone_from_slice(USER_ADVERTISE_SCAN_RESPONSE_DATA);
-//     if USER_DEVICE_NAME.len() > 0 {
-//         let adv_data_len = unsafe { cmd.fields().info.host.adv_data_len };
-//         let scan_rsp_data_len = unsafe { cmd.fields().info.host.scan_rsp_data_len };
-//         let total_adv_space = adv_data_len as u16 + 2 + USER_DEVICE_NAME.len() as u16;
-//         let total_scan_space = scan_rsp_data_len as u16 + 2 + USER_DEVICE_NAME.len() as u16;
-//         if total_adv_space <= ADV_DATA_LEN as u16 {
-//             let host = unsafe { &mut cmd.fields().info.host };
-//             let adv_data_len = &mut host.adv_data_len;
-//             let adv_data = &mut host.adv_data;
-//             append_device_name(
-//                 adv_data_len,
-//                 USER_DEVICE_NAME.len(),
-//                 &mut adv_data[*adv_data_len as usize..],
-//                 USER_DEVICE_NAME,
-//             );
-//         } else if total_scan_space <= SCAN_RSP_DATA_LEN as u16 {
-//             let host = unsafe { &mut cmd.fields().info.host };
-//             let scan_rsp_data_len = &mut host.scan_rsp_data_len;
-//             let scan_rsp_data = &mut host.scan_rsp_data;
-//             append_device_name(
-//                 scan_rsp_data_len,
-//                 USER_DEVICE_NAME.len(),
-//                 &mut scan_rsp_data[*scan_rsp_data_len as usize..],
-//                 USER_DEVICE_NAME,
-//             );
-//         }
-//     }
-//     cmd
-// }
-
-// fn app_easy_gap_directed_advertise_start_create_msg(
-//     ldc_enable: bool,
-// ) -> KeMsgGapmStartAdvertiseCmd {
-//     let mut cmd = KeMsgGapmStartAdvertiseCmd::new(TASK_APP as u16, TASK_GAPM as u16);
-//     if ldc_enable {
-//         cmd.fields().op.code = GAPM_ADV_DIRECT_LDC as u8;
-//         unsafe {
-//             cmd.fields().intv_min = USER_ADV_CONF.intv_min;
-//             cmd.fields().intv_max = USER_ADV_CONF.intv_max;
-//         }
-//     } else {
-//         cmd.fields().op.code = GAPM_ADV_DIRECT as u8;
-//         cmd.fields().intv_min = LLM_ADV_INTERVAL_MIN as u16;
-//         cmd.fields().intv_max = LLM_ADV_INTERVAL_MAX as u16;
-//     }
-//     unsafe {
-//         cmd.fields().op.addr_src = USER_ADV_CONF.addr_src;
-//         cmd.fields().channel_map = USER_ADV_CONF.channel_map;
-//         cmd.fields()
-//             .info
-//             .direct
-//             .addr
-//             .addr
-//             .copy_from_slice(&USER_ADV_CONF.peer_addr);
-//         cmd.fields().info.direct.addr_type = USER_ADV_CONF.peer_addr_type;
-//     }
-//     cmd
-// }
-
-// fn app_easy_gap_param_update_msg_create(conidx: u8) -> KeMsgGapcParamUpdateCmd {
-//     let mut cmd =
-//         KeMsgGapcParamUpdateCmd::new(TASK_APP as u16, ke_build_id!(TASK_GAPC as u8, conidx));
-//     unsafe {
-//         cmd.fields().intv_max = user_connection_param_conf.intv_max;
-//         cmd.fields().intv_min = user_connection_param_conf.intv_min;
-//         cmd.fields().latency = user_connection_param_conf.latency;
-//         cmd.fields().time_out = user_connection_param_conf.time_out;
-//         cmd.fields().ce_len_min = user_connection_param_conf.ce_len_min;
-//         cmd.fields().ce_len_max = user_connection_param_conf.ce_len_max;
-//     }
-//     cmd
-// }
-
-// fn app_easy_gap_start_connection_to_msg_create() -> KeMsgGapmStartConnectionCmd {
-//     let mut cmd = KeMsgDynGapmStartConnectionCmd::new(TASK_APP as u16, TASK_GAPM as u16);
-//     unsafe {
-//         cmd.fields().op.code = user_central_conf.code;
-//         cmd.fields().op.addr_src = user_central_conf.addr_src;
-//         cmd.fields().scan_interval = user_central_conf.scan_interval;
-//         cmd.fields().scan_window = user_central_conf.scan_window;
-//         cmd.fields().con_intv_min = user_central_conf.con_intv_min;
-//         cmd.fields().con_intv_max = user_central_conf.con_intv_max;
-//         cmd.fields().con_latency = user_central_conf.con_latency;
-//         cmd.fields().superv_to = user_central_conf.superv_to;
-//         cmd.fields().ce_len_min = user_central_conf.ce_len_min;
-//         cmd.fields().ce_len_max = user_central_conf.ce_len_max;
-//     }
-//     if (unsafe { user_central_conf.code } == GAPM_CONNECTION_DIRECT as u8)
-//         || (unsafe { user_central_conf.code } == GAPM_CONNECTION_NAME_REQUEST as u8)
-//     {
-//         cmd.fields().nb_peers = 1;
-//     } else {
-//         cmd.fields().nb_peers = CFG_MAX_CONNECTIONS as u8;
-//         let peers = unsafe {
-//             cmd.fields()
-//                 .peers
-//                 .as_mut_slice(CFG_MAX_CONNECTIONS as usize)
-//         };
-//         for i in 0..CFG_MAX_CONNECTIONS as usize {
-//             let user_central_configuration =
-//                 unsafe { &*(&user_central_conf as *const _ as *const CentralConfiguration) };
-//             peers[i]
-//                 .addr
-//                 .addr
-//                 .copy_from_slice(&user_central_configuration.peer_addresses[i].peer_addr);
-//             peers[i].addr_type = user_central_configuration.peer_addresses[i].peer_addr_type;
-//         }
-//     }
-//     cmd
-// }
-
-// pub fn app_easy_gap_advertise_stop_handler() {
-//     let app_timer_id = unsafe { &mut ADV_TIMER_ID };
-//     unsafe {
-//         app_easy_gap_advertise_stop();
-//     }
-//     *app_timer_id = 0;
-//     //     if(adv_timeout_callback!=NULL)
-//     //     {
-//     //         timeout_callback = adv_timeout_callback;
-//     //         adv_timeout_callback = NULL;
-//     //         timeout_callback();
-//     //     }
-// }
-
-// pub fn app_easy_gap_directed_advertise_start(ldc_enable: bool) {
-//     let cmd = app_easy_gap_directed_advertise_start_create_msg(ldc_enable);
-//     cmd.send();
-//     ke_state_set(TASK_APP as u16, APP_CONNECTABLE as u8);
-// }
-
-// pub fn app_easy_gap_non_connectable_advertise_start() {
-//     let cmd = app_easy_gap_non_connectable_advertise_start_create_msg();
-//     cmd.send();
-//     let state = ke_state_get(TASK_APP as u16);
-//     if state != APP_CONNECTED as u8 {
-//         ke_state_set(TASK_APP as u16, APP_CONNECTABLE as u8);
-//     }
-// }
-
-// pub fn app_easy_gap_param_update_start(conidx: u8) {
-//     app_easy_gap_param_update_msg_create(conidx).send();
-// }
-
-// pub fn app_easy_gap_start_connection_to() {
-//     app_easy_gap_start_connection_to_msg_create().send();
-// }
-
-// pub fn app_easy_gap_get_peer_features(conidx: u8) {
-//     let mut cmd = KeMsgGapcGetInfoCmd::new(TASK_APP as u16, ke_build_id!(TASK_GAPC as u8, conidx));
-//     cmd.fields().operation = GAPC_GET_PEER_FEATURES as u8;
-//     cmd.send();
-// }
-
-// #[cfg(not(feature = "exclude_dlg_timer"))]
-// pub fn app_easy_gap_undirected_advertise_with_timeout_start(delay: u32, callback: TimeoutCallback) {
-//     todo!();
-//     //     //stop the current running timer
-//     //     if(adv_timer_id != EASY_TIMER_INVALID_TIMER)
-//     //         app_easy_timer_cancel(adv_timer_id);
-//     //     if(timeout_callback != NULL)
-//     //         adv_timeout_callback = timeout_callback;
-//     //     adv_timer_id = app_easy_timer(delay, app_easy_gap_advertise_stop_handler);
-//     //     app_easy_gap_undirected_advertise_start();
-// }
-
-// #[cfg(not(feature = "exclude_dlg_timer"))]
-// pub fn app_easy_gap_advertise_with_timeout_stop() {
-//     todo!();
-//     //     //stop the current running timer
-//     //     if (adv_timer_id != EASY_TIMER_INVALID_TIMER)
-//     //     {
-//     //         app_easy_timer_cancel(adv_timer_id);
-//     //     }
-//     //     adv_timer_id = EASY_TIMER_INVALID_TIMER;
-//     //     adv_timeout_callback = NULL;
-// }
-
-// fn app_easy_gap_non_connectable_advertise_get_active() -> &'static KeMsgGapmStartAdvertiseCmd {
-//     app_easy_gap_non_connectable_advertise_start_create_msg()
-// }
-
-// fn app_easy_gap_directed_advertise_get_active(
-//     ldc_enable: bool,
-// ) -> &'static KeMsgGapmStartAdvertiseCmd {
-//     app_easy_gap_directed_advertise_start_create_msg(ldc_enable)
-// }
-
-// fn app_easy_gap_undirected_advertise_get_active() -> &'static KeMsgGapmStartAdvertiseCmd {
-//     app_easy_gap_undirected_advertise_start_create_msg()
-// }
-
-// fn app_easy_gap_param_update_get_active(conidx: u8) -> &'static KeMsgGapcParamUpdateCmd {
-//     app_easy_gap_param_update_msg_create(conidx)
-// }
-
-// fn app_easy_gap_start_connection_to_get_active() -> &'static KeMsgGapmStartConnectionCmd {
-//     app_easy_gap_start_connection_to_msg_create()
-// }
-
-// fn app_easy_gap_dev_config_get_active() -> &'static KeMsgGapmSetDevConfigCmd {
-//     app_easy_gap_dev_config_create_msg()
-// }
-
-// fn app_easy_gap_start_connection_to_set(peer_addr_type: u8, peer_addr: *const u8, intv: u16) {
-//     let mut msg = app_easy_gap_start_connection_to_msg_create();
-//     msg.fields().nb_peers = 1;
-//     let peers = unsafe { msg.fields().peers.as_mut_slice(1) };
-//     // memcpy(&msg.fields().peers[0].addr, peer_addr, BD_ADDR_LEN);
-//     // msg.fields().peers[0].addr_type = peer_addr_type;
-//     peers[0].addr_type = peer_addr_type;
-//     msg.fields().con_intv_max = intv;
-//     msg.fields().con_intv_min = intv;
-// }
+/// Connection parameters requested from the peer, in units of the HCI specification.
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectionParams {
+    /// Minimum connection interval in units of 1.25 ms
+    pub intv_min: u16,
+    /// Maximum connection interval in units of 1.25 ms
+    pub intv_max: u16,
+    /// Peripheral latency in number of connection events
+    pub latency: u16,
+    /// Supervision timeout in units of 10 ms
+    pub time_out: u16,
+    /// Minimum connection event length in units of 0.625 ms
+    pub ce_len_min: u16,
+    /// Maximum connection event length in units of 0.625 ms
+    pub ce_len_max: u16,
+}
+
+impl ConnectionParams {
+    /// Checks the parameters against the ranges allowed by the specification.
+    pub fn validate(&self) -> ParamUpdateResult {
+        if !(6..=3200).contains(&self.intv_min)
+            || !(6..=3200).contains(&self.intv_max)
+            || self.intv_min > self.intv_max
+        {
+            return Err(ParamUpdateError::InvalidInterval);
+        }
+
+        if self.latency > 499 {
+            return Err(ParamUpdateError::InvalidLatency);
+        }
+
+        // The timeout has to be longer than two effective connection intervals
+        if !(10..=3200).contains(&self.time_out)
+            || 4 * self.time_out as u32 <= (1 + self.latency as u32) * self.intv_max as u32
+        {
+            return Err(ParamUpdateError::InvalidTimeout);
+        }
+
+        if self.ce_len_min > self.ce_len_max {
+            return Err(ParamUpdateError::InvalidEventLength);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum ParamUpdateError {
+    InvalidInterval,
+    InvalidLatency,
+    InvalidTimeout,
+    InvalidEventLength,
+    /// A parameter update is pending already
+    Busy,
+}
+
+pub type ParamUpdateResult<T = ()> = Result<T, ParamUpdateError>;
+
+/// Called with the negotiated parameters or the error the update was rejected with.
+pub type ParamUpdateCallback = Box<dyn FnOnce(HlResult<GapcParamUpdatedInd>)>;
+
+/// Connection index and callback of the pending parameter update
+#[link_section = "retention_mem_area0"]
+static mut PARAM_UPDATE: Option<(u8, ParamUpdateCallback)> = None;
+
+fn app_easy_gap_param_update_create_msg(
+    conidx: u8,
+    params: &ConnectionParams,
+) -> KeMsgGapcParamUpdateCmd {
+    let mut cmd =
+        KeMsgGapcParamUpdateCmd::new(TASK_APP as u16, ke_build_id(TASK_GAPC as u8, conidx));
+
+    let msg = cmd.fields();
+
+    msg.operation = GAPC_UPDATE_PARAMS as u8;
+    msg.intv_min = params.intv_min;
+    msg.intv_max = params.intv_max;
+    msg.latency = params.latency;
+    msg.time_out = params.time_out;
+    msg.ce_len_min = params.ce_len_min;
+    msg.ce_len_max = params.ce_len_max;
+
+    cmd
+}
+
+/// Requests the peer of connection `conidx` to update the connection parameters.
+///
+/// Only a single update can be pending at a time. `callback` is called with
+/// `HlError::Gap(GapError::Disconnected)` if the connection is terminated before the update
+/// completes.
+///
+/// The SDK reports the completion of an update without a connection index, updates must
+/// therefore not be requested by other means, e.g. `app_easy_gap_param_update_start`, while
+/// this one is pending.
+pub fn request_param_update(
+    conidx: u8,
+    params: ConnectionParams,
+    callback: ParamUpdateCallback,
+) -> ParamUpdateResult {
+    params.validate()?;
+
+    let pending = unsafe { &mut PARAM_UPDATE };
+    if pending.is_some() {
+        return Err(ParamUpdateError::Busy);
+    }
+
+    *pending = Some((conidx, callback));
+
+    app_easy_gap_param_update_create_msg(conidx, &params).send();
+
+    Ok(())
+}
+
+/// Completes the pending parameter update, the indication is also received for updates initiated
+/// by the peer.
+pub(crate) fn on_param_updated_ind(conidx: u8, param: &GapcParamUpdatedInd) {
+    if let Some(callback) = take_param_update(|pending_conidx| pending_conidx == conidx) {
+        callback(Ok(*param));
+    }
+}
+
+/// Completes the pending parameter update with the parameters of its connection, in case the
+/// update completed without an indication, e.g. because the parameters did not change.
+pub(crate) fn on_update_params_complete() {
+    let pending_conidx = unsafe { PARAM_UPDATE.as_ref() }.map(|(conidx, _)| *conidx);
+
+    if let Some(connection) = pending_conidx.and_then(connections::get) {
+        if let Some(callback) = take_param_update(|_| true) {
+            callback(Ok(GapcParamUpdatedInd {
+                con_interval: connection.interval,
+                con_latency: connection.latency,
+                sup_to: connection.supervision_timeout,
+            }));
+        }
+    }
+}
+
+/// Fails the pending parameter update, the rejection is attributed to it as long as its
+/// connection exists.
+pub(crate) fn on_update_params_rejected(error: HlError) {
+    if let Some(callback) = take_param_update(|conidx| connections::get(conidx).is_some()) {
+        callback(Err(error));
+    }
+}
+
+/// Fails the pending parameter update with [`GapError::Canceled`] before the stack is reset.
+pub(crate) fn on_param_update_reset() {
+    if let Some(callback) = take_param_update(|_| true) {
+        callback(Err(HlError::Gap(GapError::Canceled)));
+    }
+}
+
+pub(crate) fn on_param_update_disconnect(conidx: u8) {
+    if let Some(callback) = take_param_update(|pending_conidx| pending_conidx == conidx) {
+        callback(Err(HlError::Gap(GapError::Disconnected)));
+    }
+}
+
+fn take_param_update(matches: impl FnOnce(u8) -> bool) -> Option<ParamUpdateCallback> {
+    let pending = unsafe { &mut PARAM_UPDATE };
+
+    match pending {
+        Some((conidx, _)) if matches(*conidx) => pending.take().map(|(_, callback)| callback),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ble_stack::host::gap::{GAP_GEN_DISCOVERABLE, GAP_LIM_DISCOVERABLE};
+
+    #[test]
+    fn adv_flags_len() {
+        // Undirected advertising with a white list filter
+        assert_eq!(app_easy_gap_adv_flags_len(GAP_NON_DISCOVERABLE as u8), 3);
+        assert_eq!(app_easy_gap_adv_flags_len(GAP_GEN_DISCOVERABLE as u8), 3);
+        assert_eq!(app_easy_gap_adv_flags_len(GAP_LIM_DISCOVERABLE as u8), 3);
+        assert_eq!(app_easy_gap_adv_flags_len(GAP_BROADCASTER_MODE as u8), 0);
+    }
+}
@@ -0,0 +1,432 @@
+//! Queries for information about a connection and its peer.
+//!
+//! Queries of the same kind are answered in the order they were issued. A query is refused with a
+//! [`QueryError`] if it cannot be issued. Issued queries complete with the error status the stack
+//! rejects them with, with [`GapError::Disconnected`] if the connection is terminated, and with
+//! [`GapError::Timeout`] if they are not answered within [`QUERY_TIMEOUT`]. Awaitable versions are
+//! provided by [`executor::gap`](crate::executor::gap).
+//!
+//! The peer features are only delivered if
+//! [`default_app_on_get_peer_features`](crate::app_modules::default_app_on_get_peer_features) is
+//! called from `app_on_get_peer_features`.
+
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{
+    app_modules::timer_queue::QueuedTimer,
+    ble_stack::{
+        host::gap::gapc::task::{
+            GapcConChannelMapInd, GapcConRssiInd, GapcPeerFeaturesInd, GapcPeerVersionInd,
+            KeMsgGapcGetInfoCmd, GAPC_GET_CON_CHANNEL_MAP, GAPC_GET_CON_RSSI,
+            GAPC_GET_PEER_FEATURES, GAPC_GET_PEER_VERSION,
+        },
+        rwble_hl::error::{GapError, HlError, HlResult},
+    },
+    platform::core_modules::{
+        ke::task::ke_build_id,
+        rwip::{TASK_APP, TASK_GAPC},
+    },
+};
+
+use super::connections;
+
+/// Time in timer units of 10 ms after which a query fails, the link layer response timeout
+pub const QUERY_TIMEOUT: u32 = 4000;
+
+pub type QueryCallback<T> = Box<dyn FnOnce(HlResult<T>)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryError {
+    /// The connection is not established
+    NotConnected,
+    /// No slot of the timer module is free for the timeout of the query
+    NoFreeSlot,
+}
+
+pub type QueryResult<T = ()> = Result<T, QueryError>;
+
+/// LE features supported by the peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerFeatures {
+    /// Feature bit mask as sent by the peer, least significant byte first
+    pub features: [u8; 8],
+}
+
+impl PeerFeatures {
+    /// Whether the feature with bit number `bit` of the LE feature mask is supported
+    pub fn supports(&self, bit: u8) -> bool {
+        self.features
+            .get(bit as usize / 8)
+            .map_or(false, |byte| byte & (1 << (bit % 8)) != 0)
+    }
+
+    pub fn encryption(&self) -> bool {
+        self.supports(0)
+    }
+
+    pub fn connection_param_request(&self) -> bool {
+        self.supports(1)
+    }
+
+    pub fn le_ping(&self) -> bool {
+        self.supports(4)
+    }
+
+    pub fn data_length_extension(&self) -> bool {
+        self.supports(5)
+    }
+
+    pub fn ll_privacy(&self) -> bool {
+        self.supports(6)
+    }
+
+    pub fn le_2m_phy(&self) -> bool {
+        self.supports(8)
+    }
+
+    pub fn le_coded_phy(&self) -> bool {
+        self.supports(11)
+    }
+}
+
+/// Version of the link layer of the peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerVersion {
+    /// Company identifier of the manufacturer
+    pub company_id: u16,
+    pub lmp_version: u8,
+    pub lmp_subversion: u16,
+}
+
+impl PeerVersion {
+    /// Major and minor version of the Bluetooth Core Specification of the LMP version
+    pub fn core_version(&self) -> Option<(u8, u8)> {
+        match self.lmp_version {
+            6 => Some((4, 0)),
+            7 => Some((4, 1)),
+            8 => Some((4, 2)),
+            9 => Some((5, 0)),
+            10 => Some((5, 1)),
+            11 => Some((5, 2)),
+            12 => Some((5, 3)),
+            13 => Some((5, 4)),
+            _ => None,
+        }
+    }
+}
+
+/// Signal strength of the connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rssi {
+    /// RSSI in dBm
+    pub rssi: i8,
+}
+
+/// Data channels used by the connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelMap {
+    /// One bit per data channel, channel 0 is the least significant bit of the first byte
+    pub map: [u8; 5],
+}
+
+impl ChannelMap {
+    pub const DATA_CHANNELS: u8 = 37;
+
+    pub fn is_used(&self, channel: u8) -> bool {
+        channel < Self::DATA_CHANNELS && self.map[channel as usize / 8] & (1 << (channel % 8)) != 0
+    }
+
+    pub fn used_channels(&self) -> u8 {
+        (0..Self::DATA_CHANNELS)
+            .filter(|channel| self.is_used(*channel))
+            .count() as u8
+    }
+}
+
+struct PendingQuery<T> {
+    id: u32,
+    conidx: u8,
+    callback: QueryCallback<T>,
+    timeout: QueuedTimer,
+}
+
+/// Callbacks of the queries of one kind, in the order they were issued
+pub(super) struct PendingQueries<T> {
+    queries: Vec<PendingQuery<T>>,
+    next_id: u32,
+}
+
+impl<T> PendingQueries<T> {
+    pub(super) const fn new() -> Self {
+        Self {
+            queries: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Queues `callback`, `expire` is called with the id of the query after [`QUERY_TIMEOUT`].
+    pub(super) fn push(
+        &mut self,
+        conidx: u8,
+        callback: QueryCallback<T>,
+        expire: fn(u32),
+    ) -> QueryResult {
+        self.next_id = self.next_id.wrapping_add(1);
+
+        let id = self.next_id;
+        let timeout = QueuedTimer::new(QUERY_TIMEOUT, Box::new(move || expire(id)))
+            .map_err(|_| QueryError::NoFreeSlot)?;
+
+        self.queries.push(PendingQuery {
+            id,
+            conidx,
+            callback,
+            timeout,
+        });
+
+        Ok(())
+    }
+
+    pub(super) fn take(&mut self, conidx: u8) -> Option<QueryCallback<T>> {
+        let idx = self
+            .queries
+            .iter()
+            .position(|query| query.conidx == conidx)?;
+
+        let query = self.queries.remove(idx);
+
+        let _ = query.timeout.cancel();
+
+        Some(query.callback)
+    }
+
+    /// Takes the query `id` whose timer has expired.
+    pub(super) fn take_expired(&mut self, id: u32) -> Option<QueryCallback<T>> {
+        let idx = self.queries.iter().position(|query| query.id == id)?;
+
+        Some(self.queries.remove(idx).callback)
+    }
+
+    /// Takes the queries of every connection.
+    pub(super) fn drain(&mut self) -> Vec<QueryCallback<T>> {
+        core::mem::take(&mut self.queries)
+            .into_iter()
+            .map(|query| {
+                let _ = query.timeout.cancel();
+                query.callback
+            })
+            .collect()
+    }
+
+    pub(super) fn take_all(&mut self, conidx: u8) -> Vec<QueryCallback<T>> {
+        let mut callbacks = Vec::new();
+
+        while let Some(callback) = self.take(conidx) {
+            callbacks.push(callback);
+        }
+
+        callbacks
+    }
+}
+
+/// Completes an expired query with [`GapError::Timeout`].
+pub(super) fn time_out<T>(callback: Option<QueryCallback<T>>) {
+    if let Some(callback) = callback {
+        callback(Err(HlError::Gap(GapError::Timeout)));
+    }
+}
+
+/// Fails the oldest query of connection `conidx` with the error of a command complete event.
+pub(super) fn fail<T>(queries: &mut PendingQueries<T>, conidx: u8, result: HlResult) {
+    if let Err(error) = result {
+        if let Some(callback) = queries.take(conidx) {
+            callback(Err(error));
+        }
+    }
+}
+
+pub(super) fn check_connected(conidx: u8) -> QueryResult {
+    if connections::get(conidx).is_some() {
+        Ok(())
+    } else {
+        Err(QueryError::NotConnected)
+    }
+}
+
+#[link_section = "retention_mem_area0"]
+static mut PEER_FEATURES: PendingQueries<PeerFeatures> = PendingQueries::new();
+
+#[link_section = "retention_mem_area0"]
+static mut PEER_VERSION: PendingQueries<PeerVersion> = PendingQueries::new();
+
+#[link_section = "retention_mem_area0"]
+static mut CON_RSSI: PendingQueries<Rssi> = PendingQueries::new();
+
+#[link_section = "retention_mem_area0"]
+static mut CON_CHANNEL_MAP: PendingQueries<ChannelMap> = PendingQueries::new();
+
+fn app_easy_gap_get_info_create_msg(conidx: u8, operation: u32) -> KeMsgGapcGetInfoCmd {
+    let mut cmd = KeMsgGapcGetInfoCmd::new(TASK_APP as u16, ke_build_id(TASK_GAPC as u8, conidx));
+
+    cmd.fields().operation = operation as u8;
+
+    cmd
+}
+
+/// Reads the LE features supported by the peer of connection `conidx`.
+pub fn app_easy_gap_get_peer_features(
+    conidx: u8,
+    callback: QueryCallback<PeerFeatures>,
+) -> QueryResult {
+    check_connected(conidx)?;
+
+    unsafe {
+        PEER_FEATURES.push(conidx, callback, |id| {
+            time_out(PEER_FEATURES.take_expired(id))
+        })
+    }?;
+
+    app_easy_gap_get_info_create_msg(conidx, GAPC_GET_PEER_FEATURES).send();
+
+    Ok(())
+}
+
+/// Reads the link layer version of the peer of connection `conidx`.
+pub fn app_easy_gap_get_peer_version(
+    conidx: u8,
+    callback: QueryCallback<PeerVersion>,
+) -> QueryResult {
+    check_connected(conidx)?;
+
+    unsafe {
+        PEER_VERSION.push(conidx, callback, |id| {
+            time_out(PEER_VERSION.take_expired(id))
+        })
+    }?;
+
+    app_easy_gap_get_info_create_msg(conidx, GAPC_GET_PEER_VERSION).send();
+
+    Ok(())
+}
+
+/// Reads the RSSI of the last packet received on connection `conidx`.
+pub fn app_easy_gap_get_con_rssi(conidx: u8, callback: QueryCallback<Rssi>) -> QueryResult {
+    check_connected(conidx)?;
+
+    unsafe { CON_RSSI.push(conidx, callback, |id| time_out(CON_RSSI.take_expired(id))) }?;
+
+    app_easy_gap_get_info_create_msg(conidx, GAPC_GET_CON_RSSI).send();
+
+    Ok(())
+}
+
+/// Reads the data channels currently used by connection `conidx`.
+pub fn app_easy_gap_get_con_channel_map(
+    conidx: u8,
+    callback: QueryCallback<ChannelMap>,
+) -> QueryResult {
+    check_connected(conidx)?;
+
+    unsafe {
+        CON_CHANNEL_MAP.push(conidx, callback, |id| {
+            time_out(CON_CHANNEL_MAP.take_expired(id))
+        })
+    }?;
+
+    app_easy_gap_get_info_create_msg(conidx, GAPC_GET_CON_CHANNEL_MAP).send();
+
+    Ok(())
+}
+
+pub(crate) fn on_peer_features_ind(conidx: u8, param: &GapcPeerFeaturesInd) {
+    if let Some(callback) = unsafe { PEER_FEATURES.take(conidx) } {
+        callback(Ok(PeerFeatures {
+            features: param.features,
+        }));
+    }
+}
+
+pub(crate) fn on_peer_version_ind(conidx: u8, param: &GapcPeerVersionInd) {
+    if let Some(callback) = unsafe { PEER_VERSION.take(conidx) } {
+        callback(Ok(PeerVersion {
+            company_id: param.compid,
+            lmp_version: param.lmp_vers,
+            lmp_subversion: param.lmp_subvers,
+        }));
+    }
+}
+
+pub(crate) fn on_con_rssi_ind(conidx: u8, param: &GapcConRssiInd) {
+    if let Some(callback) = unsafe { CON_RSSI.take(conidx) } {
+        callback(Ok(Rssi { rssi: param.rssi }));
+    }
+}
+
+pub(crate) fn on_con_channel_map_ind(conidx: u8, param: &GapcConChannelMapInd) {
+    if let Some(callback) = unsafe { CON_CHANNEL_MAP.take(conidx) } {
+        callback(Ok(ChannelMap {
+            map: param.ch_map.map,
+        }));
+    }
+}
+
+/// The stack forwards command complete events of the `GAPC_GET_*` operations to the
+/// catch-rest handler.
+pub(crate) fn on_cmp_evt(conidx: u8, operation: u8, result: HlResult) {
+    unsafe {
+        match operation as u32 {
+            GAPC_GET_PEER_FEATURES => fail(&mut PEER_FEATURES, conidx, result),
+            GAPC_GET_PEER_VERSION => fail(&mut PEER_VERSION, conidx, result),
+            GAPC_GET_CON_RSSI => fail(&mut CON_RSSI, conidx, result),
+            GAPC_GET_CON_CHANNEL_MAP => fail(&mut CON_CHANNEL_MAP, conidx, result),
+            _ => {}
+        }
+    }
+}
+
+/// Fails all queries with [`GapError::Canceled`] before the stack is reset.
+pub(crate) fn on_reset() {
+    let error = HlError::Gap(GapError::Canceled);
+
+    unsafe {
+        PEER_FEATURES
+            .drain()
+            .into_iter()
+            .for_each(|callback| callback(Err(error)));
+        PEER_VERSION
+            .drain()
+            .into_iter()
+            .for_each(|callback| callback(Err(error)));
+        CON_RSSI
+            .drain()
+            .into_iter()
+            .for_each(|callback| callback(Err(error)));
+        CON_CHANNEL_MAP
+            .drain()
+            .into_iter()
+            .for_each(|callback| callback(Err(error)));
+    }
+}
+
+pub(crate) fn on_disconnect(conidx: u8) {
+    let error = HlError::Gap(GapError::Disconnected);
+
+    unsafe {
+        PEER_FEATURES
+            .take_all(conidx)
+            .into_iter()
+            .for_each(|callback| callback(Err(error)));
+        PEER_VERSION
+            .take_all(conidx)
+            .into_iter()
+            .for_each(|callback| callback(Err(error)));
+        CON_RSSI
+            .take_all(conidx)
+            .into_iter()
+            .for_each(|callback| callback(Err(error)));
+        CON_CHANNEL_MAP
+            .take_all(conidx)
+            .into_iter()
+            .for_each(|callback| callback(Err(error)));
+    }
+}
@@ -0,0 +1,279 @@
+//! Scanning for advertising devices in the observer or central role.
+//!
+//! Advertising reports are only delivered if
+//! [`default_app_on_adv_report_ind`](crate::app_modules::default_app_on_adv_report_ind) and
+//! [`default_app_on_scanning_completed`](crate::app_modules::default_app_on_scanning_completed)
+//! are called from the corresponding app callbacks.
+
+use alloc::boxed::Box;
+
+use crate::{
+    ble_stack::{
+        host::gap::{
+            gapm::task::{
+                GapmAdvReportInd, KeMsgGapmStartScanCmd, GAPM_SCAN_ACTIVE, GAPM_SCAN_PASSIVE,
+            },
+            GAP_GEN_DISCOVERY, GAP_LIM_DISCOVERY, GAP_OBSERVER_MODE,
+        },
+        rwble_hl::error::{GapError, HlError, HlResult},
+    },
+    platform::core_modules::{
+        common::{
+            AdvReport, BDAddr, SCAN_ALLOW_ADV_ALL, SCAN_ALLOW_ADV_WLST, SCAN_FILT_DUPLIC_DIS,
+            SCAN_FILT_DUPLIC_EN,
+        },
+        rwip::{TASK_APP, TASK_GAPM},
+    },
+};
+
+use super::{ad_parser::AdParser, app_easy_gap_cancel, USER_ADV_CONF};
+
+/// Shortest scan interval and window in slots of 0.625 ms
+pub const SCAN_INTERVAL_MIN: u16 = 0x0004;
+/// Longest scan interval and window in slots of 0.625 ms
+pub const SCAN_INTERVAL_MAX: u16 = 0x4000;
+
+pub type ScanReportCallback = Box<dyn FnMut(&ScanReport)>;
+
+/// Called with the status scanning completed with, `GAP_ERR_CANCELED` if it was stopped.
+pub type ScanCompleteCallback = Box<dyn FnOnce(HlResult)>;
+
+#[derive(Debug)]
+pub enum ScanError {
+    /// The scan interval is out of range
+    InvalidInterval,
+    /// The scan window is out of range or longer than the scan interval
+    InvalidWindow,
+}
+
+pub type ScanResult<T = ()> = Result<T, ScanError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanMode {
+    /// Reports devices in general or limited discoverable mode, scanning stops after a timeout
+    GeneralDiscovery,
+    /// Reports devices in limited discoverable mode, scanning stops after a timeout
+    LimitedDiscovery,
+    /// Reports all devices until scanning is stopped
+    Observer,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScanConfiguration {
+    active: bool,
+    interval: u16,
+    window: u16,
+    mode: ScanMode,
+    filter_duplicates: bool,
+    whitelist_only: bool,
+}
+
+impl ScanConfiguration {
+    /// Passive scanning in observer mode with an interval of 100 ms and a window of 50 ms.
+    pub const fn new() -> Self {
+        Self {
+            active: false,
+            interval: 160,
+            window: 80,
+            mode: ScanMode::Observer,
+            filter_duplicates: false,
+            whitelist_only: false,
+        }
+    }
+
+    /// Active scanning requests the scan response data of scannable devices.
+    pub fn set_active(mut self, active: bool) -> Self {
+        self.active = active;
+        self
+    }
+
+    /// Interval in slots of 0.625 ms
+    pub fn set_interval(mut self, interval: u16) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Window in slots of 0.625 ms
+    pub fn set_window(mut self, window: u16) -> Self {
+        self.window = window;
+        self
+    }
+
+    pub fn set_mode(mut self, mode: ScanMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Reports each device only once per scan
+    pub fn set_filter_duplicates(mut self, filter_duplicates: bool) -> Self {
+        self.filter_duplicates = filter_duplicates;
+        self
+    }
+
+    /// Reports only devices in the white list
+    pub fn set_whitelist_only(mut self, whitelist_only: bool) -> Self {
+        self.whitelist_only = whitelist_only;
+        self
+    }
+
+    fn validate(&self) -> ScanResult {
+        if !(SCAN_INTERVAL_MIN..=SCAN_INTERVAL_MAX).contains(&self.interval) {
+            return Err(ScanError::InvalidInterval);
+        }
+
+        if self.window < SCAN_INTERVAL_MIN || self.window > self.interval {
+            return Err(ScanError::InvalidWindow);
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for ScanConfiguration {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Type of the advertising PDU a report was created from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanReportType {
+    ConnectableUndirected,
+    ConnectableDirected,
+    ScannableUndirected,
+    NonConnectableUndirected,
+    ScanResponse,
+}
+
+/// Advertising report received while scanning.
+pub struct ScanReport<'a>(&'a AdvReport);
+
+impl<'a> ScanReport<'a> {
+    pub fn report_type(&self) -> Option<ScanReportType> {
+        match self.0.evt_type {
+            0x00 => Some(ScanReportType::ConnectableUndirected),
+            0x01 => Some(ScanReportType::ConnectableDirected),
+            0x02 => Some(ScanReportType::ScannableUndirected),
+            0x03 => Some(ScanReportType::NonConnectableUndirected),
+            0x04 => Some(ScanReportType::ScanResponse),
+            _ => None,
+        }
+    }
+
+    pub fn addr(&self) -> &'a BDAddr {
+        &self.0.adv_addr
+    }
+
+    pub fn addr_type(&self) -> u8 {
+        self.0.adv_addr_type
+    }
+
+    /// RSSI in dBm
+    pub fn rssi(&self) -> i8 {
+        self.0.rssi as i8
+    }
+
+    /// Advertising or scan response data
+    pub fn data(&self) -> &'a [u8] {
+        let len = (self.0.data_len as usize).min(self.0.data.len());
+
+        &self.0.data[..len]
+    }
+
+    pub fn ad_structures(&self) -> AdParser<'a> {
+        AdParser::new(self.data())
+    }
+}
+
+#[link_section = "retention_mem_area0"]
+static mut SCAN_REPORT_CALLBACK: Option<ScanReportCallback> = None;
+
+#[link_section = "retention_mem_area0"]
+static mut SCAN_COMPLETE_CALLBACK: Option<ScanCompleteCallback> = None;
+
+fn app_easy_gap_start_scan_create_msg(config: &ScanConfiguration) -> KeMsgGapmStartScanCmd {
+    let mut cmd = KeMsgGapmStartScanCmd::new(TASK_APP as u16, TASK_GAPM as u16);
+
+    let msg = cmd.fields();
+
+    msg.op.code = if config.active {
+        GAPM_SCAN_ACTIVE as u8
+    } else {
+        GAPM_SCAN_PASSIVE as u8
+    };
+    msg.op.addr_src = unsafe { USER_ADV_CONF.addr_src };
+    msg.interval = config.interval;
+    msg.window = config.window;
+    msg.mode = match config.mode {
+        ScanMode::GeneralDiscovery => GAP_GEN_DISCOVERY as u8,
+        ScanMode::LimitedDiscovery => GAP_LIM_DISCOVERY as u8,
+        ScanMode::Observer => GAP_OBSERVER_MODE as u8,
+    };
+    msg.filt_policy = if config.whitelist_only {
+        SCAN_ALLOW_ADV_WLST as u8
+    } else {
+        SCAN_ALLOW_ADV_ALL as u8
+    };
+    msg.filter_duplic = if config.filter_duplicates {
+        SCAN_FILT_DUPLIC_EN as u8
+    } else {
+        SCAN_FILT_DUPLIC_DIS as u8
+    };
+
+    cmd
+}
+
+/// Starts scanning, `callback` is called for every advertising report until scanning completes
+/// and `complete` when it has completed.
+///
+/// The device has to be configured with the observer or central role.
+pub fn app_easy_gap_start_scan(
+    config: &ScanConfiguration,
+    callback: ScanReportCallback,
+    complete: ScanCompleteCallback,
+) -> ScanResult {
+    config.validate()?;
+
+    unsafe {
+        SCAN_REPORT_CALLBACK = Some(callback);
+        SCAN_COMPLETE_CALLBACK = Some(complete);
+    }
+
+    app_easy_gap_start_scan_create_msg(config).send();
+
+    Ok(())
+}
+
+/// Stops scanning by canceling the ongoing air operation of the GAP manager.
+pub fn app_easy_gap_stop_scan() {
+    app_easy_gap_cancel();
+}
+
+pub(crate) fn on_adv_report_ind(param: &GapmAdvReportInd) {
+    // Taken while it is called, so that the callback is able to restart scanning
+    if let Some(mut callback) = unsafe { SCAN_REPORT_CALLBACK.take() } {
+        callback(&ScanReport(&param.report));
+
+        unsafe {
+            if SCAN_REPORT_CALLBACK.is_none() {
+                SCAN_REPORT_CALLBACK = Some(callback);
+            }
+        }
+    }
+}
+
+/// Completes scanning with [`GapError::Canceled`] before the stack is reset.
+pub(crate) fn on_reset() {
+    on_scanning_completed(Err(HlError::Gap(GapError::Canceled)));
+}
+
+pub(crate) fn on_scanning_completed(result: HlResult) {
+    let callback = unsafe {
+        SCAN_REPORT_CALLBACK = None;
+        SCAN_COMPLETE_CALLBACK.take()
+    };
+
+    if let Some(callback) = callback {
+        callback(result);
+    }
+}
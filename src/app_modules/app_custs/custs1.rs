@@ -18,14 +18,13 @@ pub extern "C" fn app_custs1_create_db() {
     msg.fields().app_task = TASK_APP as u16;
     msg.fields().start_hdl = 0;
 
-    let db_cfg_ptr = &mut msg.fields().param as *mut _ as *mut Custs1DbCfg;
-
-    let db_cfg = unsafe { db_cfg_ptr.as_mut().unwrap() };
-
-    // Attribute table. In case the handle offset needs to be saved
-    db_cfg.att_tbl = core::ptr::null_mut();
-    db_cfg.cfg_flag = core::ptr::null_mut();
-    db_cfg.features = 0;
+    msg.write_tail(Custs1DbCfg {
+        max_nb_att: 0,
+        // Attribute table. In case the handle offset needs to be saved
+        att_tbl: core::ptr::null_mut(),
+        cfg_flag: core::ptr::null_mut(),
+        features: 0,
+    });
 
     msg.send();
 }
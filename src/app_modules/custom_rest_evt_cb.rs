@@ -1,4 +1,23 @@
-use crate::platform::core_modules::ke::{msg::KeMsgId, task::KeTaskId};
+use crate::{
+    app_modules::app_common::app::{self, connections, link, peer_info},
+    ble_stack::{
+        host::{
+            gap::gapc::task::{
+                GapcCmpEvt, GapcConChannelMapInd, GapcConRssiInd, GapcParamUpdatedInd,
+                GapcPeerVersionInd, GAPC_CMP_EVT, GAPC_CON_CHANNEL_MAP_IND, GAPC_CON_RSSI_IND,
+                GAPC_PARAM_UPDATED_IND, GAPC_PEER_VERSION_IND,
+            },
+            gatt::gattc::task::{
+                GattcCmpEvt, GattcMtuChangedInd, GATTC_CMP_EVT, GATTC_MTU_CHANGED_IND,
+            },
+        },
+        rwble_hl::error::HlError,
+    },
+    platform::core_modules::ke::{
+        msg::KeMsgId,
+        task::{ke_idx_get, KeTaskId},
+    },
+};
 
 #[cfg(feature = "custom_rest_evt_cb")]
 extern "Rust" {
@@ -17,6 +36,58 @@ pub extern "C" fn app_process_catch_rest_cb(
     dest_id: KeTaskId,
     src_id: KeTaskId,
 ) {
+    let conidx = ke_idx_get(src_id);
+
+    // The indications are passed on to the handlers as well
+    match msg_id as u32 {
+        GAPC_PARAM_UPDATED_IND => {
+            let param = unsafe { &*(param as *const GapcParamUpdatedInd) };
+
+            connections::on_param_updated_ind(conidx, param);
+            app::on_param_updated_ind(conidx, param);
+        }
+        GAPC_CON_RSSI_IND => {
+            let param = unsafe { &*(param as *const GapcConRssiInd) };
+
+            connections::on_con_rssi_ind(conidx, param);
+            peer_info::on_con_rssi_ind(conidx, param);
+        }
+        GAPC_PEER_VERSION_IND => {
+            peer_info::on_peer_version_ind(conidx, unsafe {
+                &*(param as *const GapcPeerVersionInd)
+            });
+        }
+        GAPC_CON_CHANNEL_MAP_IND => {
+            peer_info::on_con_channel_map_ind(conidx, unsafe {
+                &*(param as *const GapcConChannelMapInd)
+            });
+        }
+        GAPC_CMP_EVT => {
+            // The SDK handles GAPC_UPDATE_PARAMS itself and passes the other operations on
+            let param = unsafe { &*(param as *const GapcCmpEvt) };
+
+            let result = HlError::from_status(param.status);
+
+            peer_info::on_cmp_evt(conidx, param.operation, result);
+            link::on_gapc_cmp_evt(conidx, param.operation, result);
+        }
+        GATTC_MTU_CHANGED_IND => {
+            link::on_mtu_changed_ind(conidx, unsafe { &*(param as *const GattcMtuChangedInd) });
+        }
+        GATTC_CMP_EVT => {
+            let param = unsafe { &*(param as *const GattcCmpEvt) };
+
+            let result = HlError::from_status(param.status);
+
+            link::on_gattc_cmp_evt(conidx, param.operation, result);
+        }
+        _ => {}
+    }
+
+    if super::msg_dispatch::dispatch(msg_id, param) {
+        return;
+    }
+
     #[cfg(feature = "custom_rest_evt_cb")]
     {
         unsafe { user_catch_rest_hndl(msg_id, param, dest_id, src_id) };
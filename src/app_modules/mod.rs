@@ -20,7 +20,19 @@ pub use da14531_sdk_macros::{
 };
 
 use crate::{
-    ble_stack::host::gap::gapc::task::GapcConnectionReqInd,
+    ble_stack::{
+        host::gap::{
+            gapc::{
+                gapc_get_conidx,
+                task::{
+                    GapcConnectionReqInd, GapcDisconnectInd, GapcLePktSizeInd, GapcPeerFeaturesInd,
+                },
+            },
+            gapm::task::GapmAdvReportInd,
+            GAP_INVALID_CONIDX,
+        },
+        rwble_hl::error::HlError,
+    },
     platform::core_modules::{
         common::{ADV_DATA_LEN, SCAN_RSP_DATA_LEN},
         rwip::KeApiId,
@@ -30,8 +42,10 @@ use crate::{
 pub mod app;
 pub mod app_common;
 pub mod app_task;
+pub mod msg_dispatch;
 pub mod msg_utils;
 pub mod timer;
+pub mod timer_queue;
 
 mod custom_rest_evt_cb;
 
@@ -66,7 +80,11 @@ pub fn append_device_name(
 
 #[inline]
 pub fn app_env_get_conidx(conidx: u8) -> u8 {
-    unsafe { app_env.get_unchecked(conidx as usize).conidx }
+    unsafe {
+        app_env
+            .get(conidx as usize)
+            .map_or(GAP_INVALID_CONIDX as u8, |env| env.conidx)
+    }
 }
 
 #[inline]
@@ -103,10 +121,79 @@ pub fn app_easy_gap_update_adv_data(
 }
 
 #[inline]
-pub fn default_app_on_connection(conidx: u8, param: *const GapcConnectionReqInd) {
+pub fn default_app_on_connection(conidx: u8, param: &GapcConnectionReqInd) {
     unsafe {
         crate::bindings::default_app_on_connection(conidx, param);
     }
+
+    app_common::app::connections::on_connection(conidx, param);
+    app_common::app::link::on_connection(conidx);
+
+    #[cfg(not(feature = "exclude_dlg_timer"))]
+    app_common::app::app_easy_gap_advertise_with_timeout_stop();
+
+    crate::executor::gap::signal_connection(conidx);
+}
+
+#[inline]
+pub fn default_app_on_disconnect(param: &GapcDisconnectInd) {
+    unsafe {
+        crate::bindings::default_app_on_disconnect(param);
+    }
+
+    let conidx = gapc_get_conidx(param.conhdl);
+    app_common::app::connections::on_disconnect(conidx);
+    app_common::app::peer_info::on_disconnect(conidx);
+    app_common::app::link::on_disconnect(conidx);
+    app_common::app::on_param_update_disconnect(conidx);
+
+    crate::executor::gap::signal_disconnection(param);
+}
+
+/// Delivers advertising reports to the callback of
+/// [`app_easy_gap_start_scan`](app_common::app::scan::app_easy_gap_start_scan), has to be called
+/// from `app_on_adv_report_ind`.
+#[inline]
+pub fn default_app_on_adv_report_ind(param: &GapmAdvReportInd) {
+    app_common::app::scan::on_adv_report_ind(param);
+}
+
+/// Completes scanning, has to be called from `app_on_scanning_completed`.
+#[inline]
+pub fn default_app_on_scanning_completed(status: u8) {
+    app_common::app::scan::on_scanning_completed(HlError::from_status(status));
+}
+
+/// Completes a parameter update requested with
+/// [`request_param_update`](app_common::app::request_param_update), has to be called from
+/// `app_on_update_params_rejected`.
+#[inline]
+pub fn default_app_on_update_params_rejected(status: u8) {
+    app_common::app::on_update_params_rejected(HlError::from(status));
+}
+
+/// Completes a parameter update requested with
+/// [`request_param_update`](app_common::app::request_param_update) for which no
+/// `GAPC_PARAM_UPDATED_IND` was received, has to be called from `app_on_update_params_complete`.
+#[inline]
+pub fn default_app_on_update_params_complete() {
+    app_common::app::on_update_params_complete();
+}
+
+/// Completes a data length request of
+/// [`app_easy_gap_set_data_packet_length`](app_common::app::link::app_easy_gap_set_data_packet_length),
+/// has to be called from `app_on_data_length_change`.
+#[inline]
+pub fn default_app_on_data_length_change(conidx: u8, param: &GapcLePktSizeInd) {
+    app_common::app::link::on_data_length_change(conidx, param);
+}
+
+/// Completes the queries of
+/// [`app_easy_gap_get_peer_features`](app_common::app::peer_info::app_easy_gap_get_peer_features),
+/// has to be called from `app_on_get_peer_features`.
+#[inline]
+pub fn default_app_on_get_peer_features(conidx: u8, param: &GapcPeerFeaturesInd) {
+    app_common::app::peer_info::on_peer_features_ind(conidx, param);
 }
 
 #[inline]
@@ -0,0 +1,69 @@
+//! Typed handlers for the kernel messages which are not handled by the SDK.
+//!
+//! Messages without a registered handler are passed on to `user_catch_rest_hndl`.
+//!
+//! ```ignore
+//! fn on_val_write(msg: &ReceivedMessage<Custs1ValWriteInd>) { ... }
+//!
+//! register_msg_handler::<KeMsgCusts1ValWriteInd>(on_val_write);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::platform::core_modules::ke::msg::{KeMsgId, KeMsgType, ReceivedMessage};
+
+/// The message is freed by the kernel after the handler returns, it can not be forwarded.
+pub type MsgHandler<T> = fn(msg: &ReceivedMessage<T>);
+
+type Trampoline = unsafe fn(*const (), *const cty::c_void);
+
+struct Entry {
+    msg_id: KeMsgId,
+    /// Type erased `MsgHandler<T>`
+    handler: *const (),
+    trampoline: Trampoline,
+}
+
+#[link_section = "retention_mem_area0"]
+static mut HANDLERS: Vec<Entry> = Vec::new();
+
+unsafe fn trampoline<T>(handler: *const (), param: *const cty::c_void) {
+    let handler: MsgHandler<T> = core::mem::transmute(handler);
+
+    handler(&ReceivedMessage::from_raw(param));
+}
+
+/// Registers `handler` for the message `M`, replacing any handler registered before.
+pub fn register_msg_handler<M: KeMsgType>(handler: MsgHandler<M::Params>) {
+    let entry = Entry {
+        msg_id: M::ID,
+        handler: handler as *const (),
+        trampoline: trampoline::<M::Params>,
+    };
+
+    let handlers = unsafe { &mut HANDLERS };
+
+    match handlers.iter_mut().find(|e| e.msg_id == M::ID) {
+        Some(existing) => *existing = entry,
+        None => handlers.push(entry),
+    }
+}
+
+pub fn unregister_msg_handler<M: KeMsgType>() {
+    unsafe {
+        HANDLERS.retain(|e| e.msg_id != M::ID);
+    }
+}
+
+/// Calls the handler registered for `msg_id`, returns `false` if there is none.
+pub(crate) fn dispatch(msg_id: KeMsgId, param: *const cty::c_void) -> bool {
+    let entry = unsafe { HANDLERS.iter().find(|e| e.msg_id == msg_id) };
+
+    match entry {
+        Some(entry) => {
+            unsafe { (entry.trampoline)(entry.handler, param) };
+            true
+        }
+        None => false,
+    }
+}
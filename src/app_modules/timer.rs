@@ -32,28 +32,38 @@ kernel_msg_type!(private, Create, AppTimerParams, MSG_APP_CREATE_TIMER);
 kernel_msg_type!(private, Modify, AppTimerParams, MSG_APP_MODIFY_TIMER);
 kernel_msg_type!(private, Cancel, AppTimerParams, MSG_APP_CANCEL_TIMER);
 
-// pub type TimerCallback = fn();
-pub type TimerCallback = Box<dyn Fn()>;
+pub type TimerCallback = Box<dyn FnOnce()>;
+pub type PeriodicTimerCallback = Box<dyn FnMut()>;
+
+pub(crate) enum Callback {
+    Once(TimerCallback),
+    Periodic(PeriodicTimerCallback, u32),
+    /// Plain function pointers do not require any heap allocation
+    Fn(fn()),
+    PeriodicFn(fn(), u32),
+}
 
 enum TimerState {
     None,
-    Modified,
+    Modified(Callback, u32),
     Canceled,
-    Active(TimerCallback),
+    /// The callback of a periodic timer is currently executed
+    Firing,
+    Active(Callback),
 }
 
 impl PartialEq for TimerState {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (Self::Active(_), Self::Active(_)) => true,
+            (Self::Active(..), Self::Active(..)) => true,
             _ => core::mem::discriminant(self) == core::mem::discriminant(other),
         }
     }
 }
 
 impl TimerState {
-    pub fn take_callback(&mut self) -> Option<TimerCallback> {
-        if let TimerState::Active(_) = self {
+    pub fn take_callback(&mut self) -> Option<Callback> {
+        if let TimerState::Active(..) = self {
             let current_state = mem::replace(self, TimerState::None);
             if let TimerState::Active(callback) = current_state {
                 return Some(callback);
@@ -61,16 +71,53 @@ impl TimerState {
         }
         None
     }
+
+    pub fn restore_modified(&mut self) -> Option<u32> {
+        if let TimerState::Modified(..) = self {
+            let current_state = mem::replace(self, TimerState::None);
+            if let TimerState::Modified(callback, delay) = current_state {
+                *self = TimerState::Active(callback);
+                return Some(delay);
+            }
+        }
+        None
+    }
+}
+
+#[derive(Debug)]
+pub enum AppTimerError {
+    /// The timer has already expired or was canceled, its slot might be in use by another timer
+    Stale,
+    /// The delay is 0 or exceeds `KE_TIMER_DELAY_MAX`
+    InvalidDelay,
+    /// All slots of the timer module are in use
+    NoFreeSlot,
 }
 
+pub type AppTimerResult<T = ()> = Result<T, AppTimerError>;
+
 const INIT: TimerState = TimerState::None;
 #[link_section = "retention_mem_area0"]
 static mut TIMER_CALLBACKS: [TimerState; APP_MODULES_TIMER_MAX_NUM as usize] =
     [INIT; APP_MODULES_TIMER_MAX_NUM as usize];
 
-// #[link_section = "retention_mem_area0"]
-// static mut MODIFIED_TIMER_CALLBACKS: [TimerState; APP_MODULES_TIMER_MAX_NUM as usize] =
-//     [TimerState::None; APP_MODULES_TIMER_MAX_NUM as usize];
+/// Incremented every time a slot is allocated, used to detect stale timer ids.
+///
+/// The generation wraps after 65536 allocations of the same slot, a stale id is only mistaken
+/// for a live one if it is used again after exactly a multiple of that many reuses.
+#[link_section = "retention_mem_area0"]
+static mut TIMER_GENERATIONS: [u16; APP_MODULES_TIMER_MAX_NUM as usize] =
+    [0; APP_MODULES_TIMER_MAX_NUM as usize];
+
+/// Delays of the kernel timer range from 1 to `KE_TIMER_DELAY_MAX` timer units
+#[inline]
+fn validate_delay(delay: u32) -> AppTimerResult {
+    if delay > 0 && delay <= KE_TIMER_DELAY_MAX {
+        Ok(())
+    } else {
+        Err(AppTimerError::InvalidDelay)
+    }
+}
 
 #[inline]
 fn timer_handle_to_msg_id(handle: TimerHandle) -> KeMsgId {
@@ -96,90 +143,151 @@ fn is_timer_handle_valid(handle: TimerHandle) -> bool {
     (handle > 0) && (handle <= APP_MODULES_TIMER_MAX_NUM)
 }
 
-pub struct AppTimer(TimerHandle);
+#[inline]
+fn timer_handle_to_id(handle: TimerHandle) -> u32 {
+    let generation = unsafe { TIMER_GENERATIONS[timer_handle_to_index(handle)] };
+    timer_id(handle, generation)
+}
+
+#[inline]
+fn timer_id(handle: TimerHandle, generation: u16) -> u32 {
+    ((generation as u32) << 8) | handle as u32
+}
+
+#[inline]
+fn timer_id_to_handle(id: u32) -> TimerHandle {
+    (id & 0xff) as TimerHandle
+}
+
+#[inline]
+fn timer_id_to_generation(id: u32) -> u16 {
+    (id >> 8) as u16
+}
+
+/// Timer id consisting of the slot handle in the lowest byte and the slot generation above it.
+pub struct AppTimer(u32);
 
 impl AppTimer {
-    pub fn new(delay: u32, callback: TimerCallback) -> Option<Self> {
-        assert!(delay <= KE_TIMER_DELAY_MAX);
+    pub fn new(delay: u32, callback: TimerCallback) -> AppTimerResult<Self> {
+        Self::create(delay, Callback::Once(callback))
+    }
+
+    /// Creates a timer which calls `callback` every `period` timer units until it is canceled.
+    pub fn new_periodic(period: u32, callback: PeriodicTimerCallback) -> AppTimerResult<Self> {
+        Self::create(period, Callback::Periodic(callback, period))
+    }
+
+    /// Same as [`AppTimer::new`] without allocating the callback on the heap.
+    pub fn new_fn(delay: u32, callback: fn()) -> AppTimerResult<Self> {
+        Self::create(delay, Callback::Fn(callback))
+    }
+
+    /// Same as [`AppTimer::new_periodic`] without allocating the callback on the heap.
+    pub fn new_periodic_fn(period: u32, callback: fn()) -> AppTimerResult<Self> {
+        Self::create(period, Callback::PeriodicFn(callback, period))
+    }
+
+    fn create(delay: u32, callback: Callback) -> AppTimerResult<Self> {
+        validate_delay(delay)?;
+
+        let handle = register_callback(callback).ok_or(AppTimerError::NoFreeSlot)?;
+
+        create_timer(delay, handle);
+
+        Ok(Self(timer_handle_to_id(handle)))
+    }
+
+    fn handle(&self) -> TimerHandle {
+        timer_id_to_handle(self.0)
+    }
+
+    fn live_state(&self) -> AppTimerResult<&'static mut TimerState> {
+        let timer_idx = timer_handle_to_index(self.handle());
 
-        if let Some(handle) = register_callback(callback) {
-            create_timer(delay, handle);
+        if unsafe { TIMER_GENERATIONS[timer_idx] } != timer_id_to_generation(self.0) {
+            return Err(AppTimerError::Stale);
+        }
+
+        let state = unsafe { &mut TIMER_CALLBACKS[timer_idx] };
 
-            Some(Self(handle))
+        if matches!(
+            state,
+            TimerState::Active(..) | TimerState::Modified(..) | TimerState::Firing
+        ) {
+            Ok(state)
         } else {
-            None
+            Err(AppTimerError::Stale)
         }
     }
 
-    // pub fn modify(&mut self, delay: u32) {
-    //     todo!();
-    //     //     if APP_EASY_TIMER_HND_IS_VALID(handle)
-    //     //     {
-    //     //         if ((timer_callbacks[APP_EASY_TIMER_HND_TO_IDX(handle)] != NULL) &&
-    //     //             (timer_callbacks[APP_EASY_TIMER_HND_TO_IDX(handle)] != timer_canceled_handler))
-    //     //         {
-    //     //             // Remove the timer from the timer queue
-    //     //             ke_timer_clear(APP_EASY_TIMER_HND_TO_MSG_ID(handle), TASK_APP);
-    //     //             timer_callbacks[APP_EASY_TIMER_HND_TO_IDX(handle)] = timer_canceled_handler;
-    //     //             /*
-    //     //                 Send a message to the kernel in order to clear the timer callback function and
-    //     //                 free the respective position in the timers callback array.
-    //     //                 The app_easy_timer_cancel() function cannot guarantee if a timer has entered
-    //     //                 the message queue or not. Therefore a message must be sent to the kernel and
-    //     //                 inform it about the requested cancel operation.
-    //     //             */
-    //     //             struct cancel_timer_struct *req = KE_MSG_ALLOC(APP_CANCEL_TIMER, TASK_APP, TASK_APP,
-    //     //                                                            cancel_timer_struct);
-    //     //             req->handle = handle;
-    //     //             ke_msg_send(req);
-    //     //         }
-    //     //         else
-    //     //         {
-    //     //             ASSERT_WARNING(0);
-    //     //         }
-    //     //    }
-    //     //    else
-    //     //    {
-    //     //        ASSERT_WARNING(0);
-    //     //    }
-    // }
-
-    pub fn cancel(self) {
-        // assert!(is_timer_handle_valid(self.0));
-
-        let timer_idx = timer_handle_to_index(self.0);
-
-        let callback = unsafe { &TIMER_CALLBACKS[timer_idx] };
-
-        if *callback != TimerState::None && *callback != TimerState::Modified {
+    pub fn modify(&mut self, delay: u32) -> AppTimerResult {
+        validate_delay(delay)?;
+
+        let state = self.live_state()?;
+
+        if let TimerState::Modified(_, pending_delay) = state {
+            // The re-creation is still pending, it will pick up the new delay
+            *pending_delay = delay;
+        } else if *state == TimerState::Firing {
+            // Called from within the callback of a periodic timer, the next expiry has just been
+            // set by the kernel handler and can therefore be replaced directly.
+            ke_timer_set(
+                timer_handle_to_msg_id(self.handle()),
+                TASK_APP as u16,
+                delay,
+            );
+        } else if let Some(callback) = state.take_callback() {
             // Remove the timer from the timer queue
-            ke_timer_clear(timer_handle_to_msg_id(self.0), TASK_APP as u16);
+            ke_timer_clear(timer_handle_to_msg_id(self.handle()), TASK_APP as u16);
 
-            unsafe {
-                TIMER_CALLBACKS[timer_idx] = TimerState::Canceled;
-            }
+            // Keep the callback until the timer is re-created with the new delay
+            *state = TimerState::Modified(callback, delay);
 
             /*
-                Send a message to the kernel in order to clear the timer callback function and
-                free the respective position in the timers callback array.
-                The AppTimer::cancel() function cannot guarantee if a timer has entered
-                the message queue or not. Therefore a message must be sent to the kernel and
-                inform it about the requested cancel operation.
+                As with AppTimer::cancel(), the timer might already have entered the message
+                queue. The cancel message is processed after any pending timer message, the
+                handler then re-creates the timer with the new delay.
             */
             let mut msg = KeMsgCancelAppTimerParams::new(TASK_APP as u16, TASK_APP as u16);
 
-            msg.fields().handle = self.0;
+            msg.fields().handle = self.handle();
 
             msg.send();
         }
+
+        Ok(())
+    }
+
+    pub fn cancel(self) -> AppTimerResult {
+        let state = self.live_state()?;
+
+        // Remove the timer from the timer queue
+        ke_timer_clear(timer_handle_to_msg_id(self.handle()), TASK_APP as u16);
+
+        *state = TimerState::Canceled;
+
+        /*
+            Send a message to the kernel in order to clear the timer callback function and
+            free the respective position in the timers callback array.
+            The AppTimer::cancel() function cannot guarantee if a timer has entered
+            the message queue or not. Therefore a message must be sent to the kernel and
+            inform it about the requested cancel operation.
+        */
+        let mut msg = KeMsgCancelAppTimerParams::new(TASK_APP as u16, TASK_APP as u16);
+
+        msg.fields().handle = self.handle();
+
+        msg.send();
+
+        Ok(())
     }
 
     pub fn cancel_all() {
         for (timer_idx, callback) in unsafe { TIMER_CALLBACKS.iter().enumerate() } {
             if *callback != TimerState::None && *callback != TimerState::Canceled {
                 let handle = timer_index_to_handle(timer_idx);
-                let timer = AppTimer(handle);
-                timer.cancel();
+                let timer = AppTimer(timer_handle_to_id(handle));
+                let _ = timer.cancel();
             }
         }
     }
@@ -207,9 +315,8 @@ pub unsafe extern "C" fn app_timer_api_process_handler(
             ProcessEventResponse::PR_EVENT_HANDLED
         }
         AppMsg::APP_MODIFY_TIMER => {
-            // *msg_ret = modify_timer_handler(timer_params.handle, timer_params.delay);
-            // ProcessEventResponse::PR_EVENT_HANDLED
-            ProcessEventResponse::PR_EVENT_UNHANDLED
+            *msg_ret = modify_timer_handler(timer_params.handle);
+            ProcessEventResponse::PR_EVENT_HANDLED
         }
         _ => {
             let msg_id = msg_id as KeMsgId;
@@ -231,53 +338,75 @@ fn create_timer_handler(handle: TimerHandle, delay: u32) -> KeMsgStatusTag {
 }
 
 fn cancel_timer_handler(handle: TimerHandle) -> KeMsgStatusTag {
-    assert!(is_timer_handle_valid(handle));
+    if !is_timer_handle_valid(handle) {
+        return KE_MSG_CONSUMED;
+    }
     let timer_idx = timer_handle_to_index(handle);
     let callback = unsafe { &mut TIMER_CALLBACKS[timer_idx] };
     if *callback == TimerState::Canceled {
         *callback = TimerState::None;
-        //         modified_timer_callbacks[i] = NULL;
-    } else if *callback == TimerState::Modified {
+    } else if let TimerState::Modified(_, delay) = callback {
         let mut msg = KeMsgModifyAppTimerParams::new(TASK_APP as u16, TASK_APP as u16);
         msg.fields().handle = handle;
-        //         req->delay = param->delay;
+        msg.fields().delay = *delay;
         msg.send();
     }
     KE_MSG_CONSUMED
 }
 
-// fn modify_timer_handler(handle: TimerHandle, delay: u32) -> KeMsgStatusTag {
-//     // if APP_EASY_TIMER_HND_IS_VALID(param->handle)
-//     // {
-//     //     int i = APP_EASY_TIMER_HND_TO_IDX(param->handle);
-//     //     if (timer_callbacks[i] == timer_modified_handler)
-//     //     {
-//     //         // Restore timer callback function
-//     //         timer_callbacks[i] = modified_timer_callbacks[i];
-//     //         // Re-create the timer with new delay
-//     //         create_timer(param->handle, param->delay);
-//     //     }
-//     // }
-//     // else
-//     // {
-//     //     ASSERT_ERROR(0);
-//     // }
-//     KeMsgStatusTag::KE_MSG_CONSUMED
-// }
+fn modify_timer_handler(handle: TimerHandle) -> KeMsgStatusTag {
+    if !is_timer_handle_valid(handle) {
+        return KE_MSG_CONSUMED;
+    }
+    let callback = unsafe { &mut TIMER_CALLBACKS[timer_handle_to_index(handle)] };
+
+    // Restore the timer callback and re-create the timer with the latest requested delay
+    if let Some(delay) = callback.restore_modified() {
+        create_timer(delay, handle);
+    }
+
+    KE_MSG_CONSUMED
+}
 
 fn call_timer_callback_handler(handle: TimerHandle) -> KeMsgStatusTag {
-    let callback = unsafe { &mut TIMER_CALLBACKS[timer_handle_to_index(handle)] };
+    let state = unsafe { &mut TIMER_CALLBACKS[timer_handle_to_index(handle)] };
 
-    if *callback != TimerState::Canceled && *callback != TimerState::Modified {
-        if let Some(callback) = callback.take_callback() {
-            // //                 modified_timer_callbacks[APP_EASY_TIMER_HND_TO_IDX(handle)] = NULL;
+    match state.take_callback() {
+        Some(Callback::Once(callback)) => callback(),
+        Some(Callback::Fn(callback)) => callback(),
+        Some(Callback::Periodic(mut callback, period)) => {
+            rearm_periodic(handle, period);
             callback();
+            restore_periodic(handle, Callback::Periodic(callback, period));
         }
+        Some(Callback::PeriodicFn(callback, period)) => {
+            rearm_periodic(handle, period);
+            callback();
+            restore_periodic(handle, Callback::PeriodicFn(callback, period));
+        }
+        None => {}
     }
 
     KE_MSG_CONSUMED
 }
 
+/// Re-arm before calling back so that the callback is able to modify or cancel the timer
+fn rearm_periodic(handle: TimerHandle, period: u32) {
+    unsafe {
+        TIMER_CALLBACKS[timer_handle_to_index(handle)] = TimerState::Firing;
+    }
+    ke_timer_set(timer_handle_to_msg_id(handle), TASK_APP as u16, period);
+}
+
+/// A cancel request from within the callback leaves the slot in the canceled state
+/// until the cancel message is processed, the callback is dropped in that case.
+fn restore_periodic(handle: TimerHandle, callback: Callback) {
+    let state = unsafe { &mut TIMER_CALLBACKS[timer_handle_to_index(handle)] };
+    if *state == TimerState::Firing {
+        *state = TimerState::Active(callback);
+    }
+}
+
 fn create_timer(delay: u32, handle: TimerHandle) {
     if app_check_ble_active() {
         let msg_id = timer_handle_to_msg_id(handle);
@@ -295,10 +424,13 @@ fn create_timer(delay: u32, handle: TimerHandle) {
 }
 
 #[inline]
-fn register_callback(callback: TimerCallback) -> Option<TimerHandle> {
+fn register_callback(callback: Callback) -> Option<TimerHandle> {
     for (idx, callback_entry) in unsafe { TIMER_CALLBACKS.iter_mut().enumerate() } {
         if *callback_entry == TimerState::None {
             *callback_entry = TimerState::Active(callback);
+            unsafe {
+                TIMER_GENERATIONS[idx] = TIMER_GENERATIONS[idx].wrapping_add(1);
+            }
             return Some(timer_index_to_handle(idx));
         }
     }
@@ -308,3 +440,28 @@ fn register_callback(callback: TimerCallback) -> Option<TimerHandle> {
 // fn unregister_callback(handle: TimerHandle) {
 //     let callback_entry = unsafe { TIMER_CALLBACKS.get_mut(handle as usize) };
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timer_id_round_trip() {
+        for generation in [0, 1, 0xff, 0x100, u16::MAX] {
+            let id = timer_id(APP_MODULES_TIMER_MAX_NUM, generation);
+            assert_eq!(timer_id_to_handle(id), APP_MODULES_TIMER_MAX_NUM);
+            assert_eq!(timer_id_to_generation(id), generation);
+        }
+    }
+
+    #[test]
+    fn timer_id_differs_until_generation_wraps() {
+        let first = timer_id(1, 0);
+        let mut generation = 0u16;
+        for _ in 0..u16::MAX {
+            generation = generation.wrapping_add(1);
+            assert_ne!(timer_id(1, generation), first);
+        }
+        assert_eq!(timer_id(1, generation.wrapping_add(1)), first);
+    }
+}
@@ -0,0 +1,340 @@
+//! Software timers multiplexed onto a single [`AppTimer`].
+//!
+//! The number of queued timers is only limited by the heap, timers expire in the order of their
+//! deadlines and timers with equal deadlines expire in the order they were created. Delays are
+//! limited to `KE_TIMER_DELAY_MAX` so that deadlines remain comparable on the wrapping kernel time.
+
+use alloc::vec::Vec;
+
+use crate::{
+    bindings::KE_TIMER_DELAY_MAX,
+    platform::core_modules::ke::timer::{ke_time, ke_time_add, ke_time_diff},
+};
+
+use super::timer::{
+    AppTimer, AppTimerError, AppTimerResult, Callback, PeriodicTimerCallback, TimerCallback,
+};
+
+struct Entry {
+    id: u32,
+    /// Kernel time at which the timer expires
+    expiry: u32,
+    /// `None` while the callback of a periodic timer is executed
+    callback: Option<Callback>,
+}
+
+#[link_section = "retention_mem_area0"]
+static mut QUEUE: Vec<Entry> = Vec::new();
+
+#[link_section = "retention_mem_area0"]
+static mut KERNEL_TIMER: Option<AppTimer> = None;
+
+#[link_section = "retention_mem_area0"]
+static mut NEXT_ID: u32 = 0;
+
+/// Remaining time until `expiry`, negative if it has already passed
+#[inline]
+fn remaining(expiry: u32, now: u32) -> i32 {
+    ke_time_diff(expiry, now)
+}
+
+pub struct QueuedTimer(u32);
+
+impl QueuedTimer {
+    /// Creates a timer which calls `callback` once after `delay` timer units.
+    pub fn new(delay: u32, callback: TimerCallback) -> AppTimerResult<Self> {
+        Self::create(delay, Callback::Once(callback))
+    }
+
+    /// Creates a timer which calls `callback` every `period` timer units until it is canceled.
+    pub fn new_periodic(period: u32, callback: PeriodicTimerCallback) -> AppTimerResult<Self> {
+        validate_period(period)?;
+
+        Self::create(period, Callback::Periodic(callback, period))
+    }
+
+    /// Same as [`QueuedTimer::new`] without allocating the callback on the heap.
+    pub fn new_fn(delay: u32, callback: fn()) -> AppTimerResult<Self> {
+        Self::create(delay, Callback::Fn(callback))
+    }
+
+    /// Same as [`QueuedTimer::new_periodic`] without allocating the callback on the heap.
+    pub fn new_periodic_fn(period: u32, callback: fn()) -> AppTimerResult<Self> {
+        validate_period(period)?;
+
+        Self::create(period, Callback::PeriodicFn(callback, period))
+    }
+
+    fn create(delay: u32, callback: Callback) -> AppTimerResult<Self> {
+        validate_delay(delay)?;
+
+        let id = unsafe {
+            NEXT_ID = NEXT_ID.wrapping_add(1);
+            NEXT_ID
+        };
+
+        insert(Entry {
+            id,
+            expiry: ke_time_add(ke_time(), delay),
+            callback: Some(callback),
+        });
+
+        // The queue depends on a free slot of the timer module while it is not empty
+        if reschedule() {
+            Ok(Self(id))
+        } else {
+            remove(id);
+            Err(AppTimerError::NoFreeSlot)
+        }
+    }
+
+    /// Restarts the timer so that it expires `delay` timer units from now.
+    ///
+    /// Fails with [`AppTimerError::NoFreeSlot`] if the kernel timer of the queue could not be
+    /// re-armed, the timer stays queued and expires with the next successful re-arm.
+    pub fn modify(&mut self, delay: u32) -> AppTimerResult {
+        validate_delay(delay)?;
+
+        let mut entry = remove(self.0).ok_or(AppTimerError::Stale)?;
+
+        entry.expiry = ke_time_add(ke_time(), delay);
+        insert(entry);
+
+        if reschedule() {
+            Ok(())
+        } else {
+            Err(AppTimerError::NoFreeSlot)
+        }
+    }
+
+    /// Fails with [`AppTimerError::NoFreeSlot`] if the kernel timer could not be re-armed for
+    /// the remaining timers, the timer itself is canceled in any case.
+    pub fn cancel(self) -> AppTimerResult {
+        remove(self.0).ok_or(AppTimerError::Stale)?;
+
+        if reschedule() {
+            Ok(())
+        } else {
+            Err(AppTimerError::NoFreeSlot)
+        }
+    }
+
+    pub fn cancel_all() {
+        unsafe {
+            QUEUE.clear();
+        }
+        reschedule();
+    }
+}
+
+#[inline]
+fn validate_delay(delay: u32) -> AppTimerResult {
+    if delay <= KE_TIMER_DELAY_MAX {
+        Ok(())
+    } else {
+        Err(AppTimerError::InvalidDelay)
+    }
+}
+
+#[inline]
+fn validate_period(period: u32) -> AppTimerResult {
+    if period > 0 {
+        validate_delay(period)
+    } else {
+        Err(AppTimerError::InvalidDelay)
+    }
+}
+
+fn insert(entry: Entry) {
+    insert_sorted(unsafe { &mut QUEUE }, entry, ke_time());
+}
+
+/// Inserts `entry` behind all entries which expire no later than it.
+fn insert_sorted(queue: &mut Vec<Entry>, entry: Entry, now: u32) {
+    let entry_remaining = remaining(entry.expiry, now);
+
+    let position = queue
+        .iter()
+        .position(|e| remaining(e.expiry, now) > entry_remaining)
+        .unwrap_or(queue.len());
+
+    queue.insert(position, entry);
+}
+
+fn remove(id: u32) -> Option<Entry> {
+    let queue = unsafe { &mut QUEUE };
+
+    queue
+        .iter()
+        .position(|e| e.id == id)
+        .map(|position| queue.remove(position))
+}
+
+/// Arms the kernel timer for the head of the queue or releases it if the queue is empty.
+fn reschedule() -> bool {
+    match unsafe { QUEUE.first() } {
+        Some(head) => schedule(remaining(head.expiry, ke_time()).max(0) as u32),
+        None => {
+            if let Some(timer) = unsafe { KERNEL_TIMER.take() } {
+                let _ = timer.cancel();
+            }
+            true
+        }
+    }
+}
+
+fn schedule(delay: u32) -> bool {
+    let delay = delay.clamp(1, KE_TIMER_DELAY_MAX);
+
+    let kernel_timer = unsafe { &mut KERNEL_TIMER };
+
+    if let Some(timer) = kernel_timer {
+        if timer.modify(delay).is_ok() {
+            return true;
+        }
+        // The timer has been released behind our back, fall back to creating a new one
+        *kernel_timer = None;
+    }
+
+    *kernel_timer = AppTimer::new_fn(delay, expire).ok();
+    kernel_timer.is_some()
+}
+
+fn expire() {
+    // The kernel timer is a one-shot timer and has been released by the timer module
+    unsafe {
+        KERNEL_TIMER = None;
+    }
+
+    loop {
+        let queue = unsafe { &mut QUEUE };
+        let now = ke_time();
+
+        match queue.first() {
+            Some(head) if remaining(head.expiry, now) <= 0 => {}
+            _ => break,
+        }
+
+        let mut entry = queue.remove(0);
+
+        match entry.callback.take() {
+            Some(Callback::Once(callback)) => callback(),
+            Some(Callback::Fn(callback)) => callback(),
+            Some(Callback::Periodic(mut callback, period)) => {
+                let id = rearm_periodic(entry, now, period);
+                callback();
+                restore_periodic(id, Callback::Periodic(callback, period));
+            }
+            Some(Callback::PeriodicFn(callback, period)) => {
+                let id = rearm_periodic(entry, now, period);
+                callback();
+                restore_periodic(id, Callback::PeriodicFn(callback, period));
+            }
+            None => {}
+        }
+    }
+
+    reschedule();
+}
+
+/// Re-queue before calling back so that the callback is able to modify or cancel the timer
+fn rearm_periodic(mut entry: Entry, now: u32, period: u32) -> u32 {
+    let id = entry.id;
+
+    entry.expiry = ke_time_add(now, period);
+    insert(entry);
+
+    id
+}
+
+/// The callback is dropped if the timer was canceled from within the callback.
+fn restore_periodic(id: u32, callback: Callback) {
+    if let Some(entry) = unsafe { QUEUE.iter_mut().find(|e| e.id == id) } {
+        entry.callback = Some(callback);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::platform::core_modules::ke::timer::KE_TIME_MASK;
+
+    fn entry(id: u32, expiry: u32) -> Entry {
+        Entry {
+            id,
+            expiry,
+            callback: Some(Callback::Fn(|| {})),
+        }
+    }
+
+    fn ids(queue: &[Entry]) -> Vec<u32> {
+        queue.iter().map(|e| e.id).collect()
+    }
+
+    #[test]
+    fn orders_by_expiry() {
+        let mut queue = Vec::new();
+
+        insert_sorted(&mut queue, entry(1, 300), 100);
+        insert_sorted(&mut queue, entry(2, 150), 100);
+        insert_sorted(&mut queue, entry(3, 200), 100);
+
+        assert_eq!(ids(&queue), [2, 3, 1]);
+    }
+
+    #[test]
+    fn equal_expiries_keep_creation_order() {
+        let mut queue = Vec::new();
+
+        insert_sorted(&mut queue, entry(1, 200), 100);
+        insert_sorted(&mut queue, entry(2, 200), 100);
+        insert_sorted(&mut queue, entry(3, 150), 100);
+        insert_sorted(&mut queue, entry(4, 200), 100);
+
+        assert_eq!(ids(&queue), [3, 1, 2, 4]);
+    }
+
+    #[test]
+    fn expired_entries_come_first() {
+        let mut queue = Vec::new();
+
+        insert_sorted(&mut queue, entry(1, 120), 100);
+        insert_sorted(&mut queue, entry(2, 90), 100);
+
+        assert_eq!(ids(&queue), [2, 1]);
+        assert_eq!(remaining(90, 100), -10);
+    }
+
+    #[test]
+    fn time_wraps_at_mask() {
+        assert_eq!(ke_time_add(KE_TIME_MASK, 1), 0);
+        assert_eq!(ke_time_add(KE_TIME_MASK - 5, 10), 4);
+        assert_eq!(ke_time_add(10, KE_TIMER_DELAY_MAX), 10 + KE_TIMER_DELAY_MAX);
+    }
+
+    #[test]
+    fn remaining_across_wrap() {
+        let now = KE_TIME_MASK - 5;
+
+        assert_eq!(remaining(ke_time_add(now, 10), now), 10);
+        assert_eq!(remaining(now, ke_time_add(now, 10)), -10);
+        assert_eq!(
+            remaining(ke_time_add(now, KE_TIMER_DELAY_MAX), now),
+            KE_TIMER_DELAY_MAX as i32
+        );
+    }
+
+    #[test]
+    fn orders_across_wrap() {
+        let now = KE_TIME_MASK - 5;
+        let mut queue = Vec::new();
+
+        insert_sorted(&mut queue, entry(1, ke_time_add(now, 20)), now);
+        insert_sorted(&mut queue, entry(2, ke_time_add(now, 3)), now);
+        insert_sorted(&mut queue, entry(3, ke_time_add(now, 10)), now);
+
+        assert_eq!(ids(&queue), [2, 3, 1]);
+        assert!(queue[1].expiry < queue[0].expiry);
+    }
+}
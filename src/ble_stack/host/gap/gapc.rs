@@ -1,16 +1,50 @@
+#[inline]
+pub fn gapc_get_conidx(conhdl: u16) -> u8 {
+    unsafe { crate::bindings::gapc_get_conidx(conhdl) }
+}
+
+/// Link security level of the connection `conidx`, one of `GAP_LK_*`
+#[inline]
+pub fn gapc_lk_sec_lvl_get(conidx: u8) -> u8 {
+    unsafe { crate::bindings::gapc_lk_sec_lvl_get(conidx) }
+}
+
 pub mod task {
     pub use crate::bindings::{
-        gapc_connection_req_ind as GapcConnectionReqInd, gapc_disconnect_ind as GapcDisconnectInd,
-        gapc_msg_id as GapcMsgId, gapc_msg_id_GAPC_GET_INFO_CMD as GAPC_GET_INFO_CMD,
+        gapc_cmp_evt as GapcCmpEvt, gapc_con_channel_map_ind as GapcConChannelMapInd,
+        gapc_con_rssi_ind as GapcConRssiInd, gapc_connection_req_ind as GapcConnectionReqInd,
+        gapc_disconnect_ind as GapcDisconnectInd, gapc_get_info_cmd as GapcGetInfoCmd,
+        gapc_le_pkt_size_ind as GapcLePktSizeInd, gapc_msg_id as GapcMsgId,
+        gapc_msg_id_GAPC_CMP_EVT as GAPC_CMP_EVT,
+        gapc_msg_id_GAPC_CON_CHANNEL_MAP_IND as GAPC_CON_CHANNEL_MAP_IND,
+        gapc_msg_id_GAPC_CON_RSSI_IND as GAPC_CON_RSSI_IND,
+        gapc_msg_id_GAPC_GET_INFO_CMD as GAPC_GET_INFO_CMD,
+        gapc_msg_id_GAPC_LE_PKT_SIZE_IND as GAPC_LE_PKT_SIZE_IND,
+        gapc_msg_id_GAPC_PARAM_UPDATED_IND as GAPC_PARAM_UPDATED_IND,
         gapc_msg_id_GAPC_PARAM_UPDATE_CMD as GAPC_PARAM_UPDATE_CMD,
-        gapc_param_update_cmd as GapcParamUpdateCmd, gapc_param_updated_ind as GapcParamUpdatedInd,
-        gapc_get_info_cmd as GapcGetInfoCmd,
+        gapc_msg_id_GAPC_PEER_FEATURES_IND as GAPC_PEER_FEATURES_IND,
+        gapc_msg_id_GAPC_PEER_VERSION_IND as GAPC_PEER_VERSION_IND,
+        gapc_msg_id_GAPC_SET_LE_PKT_SIZE_CMD as GAPC_SET_LE_PKT_SIZE_CMD,
+        gapc_operation_GAPC_GET_CON_CHANNEL_MAP as GAPC_GET_CON_CHANNEL_MAP,
+        gapc_operation_GAPC_GET_CON_RSSI as GAPC_GET_CON_RSSI,
         gapc_operation_GAPC_GET_PEER_FEATURES as GAPC_GET_PEER_FEATURES,
-        gapc_msg_id_GAPC_PARAM_UPDATED_IND as GAPC_PARAM_UPDATED_IND
-
+        gapc_operation_GAPC_GET_PEER_VERSION as GAPC_GET_PEER_VERSION,
+        gapc_operation_GAPC_SET_LE_PKT_SIZE as GAPC_SET_LE_PKT_SIZE,
+        gapc_operation_GAPC_UPDATE_PARAMS as GAPC_UPDATE_PARAMS,
+        gapc_param_update_cmd as GapcParamUpdateCmd, gapc_param_updated_ind as GapcParamUpdatedInd,
+        gapc_peer_features_ind as GapcPeerFeaturesInd, gapc_peer_version_ind as GapcPeerVersionInd,
+        gapc_set_le_pkt_size_cmd as GapcSetLePktSizeCmd,
     };
     use crate::platform::core_modules::ke::msg::kernel_msg_type;
 
     kernel_msg_type!(GapcParamUpdateCmd, GAPC_PARAM_UPDATE_CMD);
     kernel_msg_type!(GapcGetInfoCmd, GAPC_GET_INFO_CMD);
+    kernel_msg_type!(GapcParamUpdatedInd, GAPC_PARAM_UPDATED_IND);
+    kernel_msg_type!(GapcConRssiInd, GAPC_CON_RSSI_IND);
+    kernel_msg_type!(GapcPeerFeaturesInd, GAPC_PEER_FEATURES_IND);
+    kernel_msg_type!(GapcPeerVersionInd, GAPC_PEER_VERSION_IND);
+    kernel_msg_type!(GapcConChannelMapInd, GAPC_CON_CHANNEL_MAP_IND);
+    kernel_msg_type!(GapcCmpEvt, GAPC_CMP_EVT);
+    kernel_msg_type!(GapcSetLePktSizeCmd, GAPC_SET_LE_PKT_SIZE_CMD);
+    kernel_msg_type!(GapcLePktSizeInd, GAPC_LE_PKT_SIZE_IND);
 }
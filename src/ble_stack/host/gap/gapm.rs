@@ -1,28 +1,83 @@
 pub mod task {
     pub use crate::bindings::{
+        gapm_adv_host as GapmAdvHost, gapm_adv_report_ind as GapmAdvReportInd,
+        gapm_att_cfg_flag_GAPM_MASK_ATT_APPEARENCE_PERM as GAPM_MASK_ATT_APPEARENCE_PERM,
+        gapm_att_cfg_flag_GAPM_MASK_ATT_NAME_PERM as GAPM_MASK_ATT_NAME_PERM,
+        gapm_att_cfg_flag_GAPM_MASK_ATT_SLV_PREF_CON_PAR_EN as GAPM_MASK_ATT_SLV_PREF_CON_PAR_EN,
         gapm_att_cfg_flag_GAPM_MASK_ATT_SVC_CHG_EN as GAPM_MASK_ATT_SVC_CHG_EN,
+        gapm_att_cfg_flag_GAPM_POS_ATT_APPEARENCE_PERM as GAPM_POS_ATT_APPEARENCE_PERM,
+        gapm_att_cfg_flag_GAPM_POS_ATT_NAME_PERM as GAPM_POS_ATT_NAME_PERM,
+        gapm_cancel_cmd as GapmCancelCmd, gapm_msg_id_GAPM_ADV_REPORT_IND as GAPM_ADV_REPORT_IND,
+        gapm_msg_id_GAPM_CANCEL_CMD as GAPM_CANCEL_CMD,
+        gapm_msg_id_GAPM_PEER_NAME_IND as GAPM_PEER_NAME_IND,
         gapm_msg_id_GAPM_PROFILE_TASK_ADD_CMD as GAPM_PROFILE_TASK_ADD_CMD,
+        gapm_msg_id_GAPM_RESET_CMD as GAPM_RESET_CMD,
         gapm_msg_id_GAPM_SET_DEV_CONFIG_CMD as GAPM_SET_DEV_CONFIG_CMD,
         gapm_msg_id_GAPM_START_ADVERTISE_CMD as GAPM_START_ADVERTISE_CMD,
         gapm_msg_id_GAPM_START_CONNECTION_CMD as GAPM_START_CONNECTION_CMD,
+        gapm_msg_id_GAPM_START_SCAN_CMD as GAPM_START_SCAN_CMD,
+        gapm_msg_id_GAPM_UPDATE_ADVERTISE_DATA_CMD as GAPM_UPDATE_ADVERTISE_DATA_CMD,
         gapm_operation_GAPM_ADV_DIRECT as GAPM_ADV_DIRECT,
         gapm_operation_GAPM_ADV_DIRECT_LDC as GAPM_ADV_DIRECT_LDC,
         gapm_operation_GAPM_ADV_NON_CONN as GAPM_ADV_NON_CONN,
         gapm_operation_GAPM_ADV_UNDIRECT as GAPM_ADV_UNDIRECT,
+        gapm_operation_GAPM_CANCEL as GAPM_CANCEL,
+        gapm_operation_GAPM_CONNECTION_AUTO as GAPM_CONNECTION_AUTO,
         gapm_operation_GAPM_CONNECTION_DIRECT as GAPM_CONNECTION_DIRECT,
         gapm_operation_GAPM_CONNECTION_NAME_REQUEST as GAPM_CONNECTION_NAME_REQUEST,
         gapm_operation_GAPM_PROFILE_TASK_ADD as GAPM_PROFILE_TASK_ADD,
+        gapm_operation_GAPM_RESET as GAPM_RESET,
+        gapm_operation_GAPM_SCAN_ACTIVE as GAPM_SCAN_ACTIVE,
+        gapm_operation_GAPM_SCAN_PASSIVE as GAPM_SCAN_PASSIVE,
         gapm_operation_GAPM_SET_DEV_CONFIG as GAPM_SET_DEV_CONFIG,
-        gapm_profile_task_add_cmd as GapmProfileTaskAdd,
-        gapm_set_dev_config_cmd as GapmSetDevConfigCmd,
+        gapm_operation_GAPM_UPDATE_ADVERTISE_DATA as GAPM_UPDATE_ADVERTISE_DATA,
+        gapm_peer_name_ind as GapmPeerNameInd, gapm_profile_task_add_cmd as GapmProfileTaskAdd,
+        gapm_reset_cmd as GapmResetCmd, gapm_set_dev_config_cmd as GapmSetDevConfigCmd,
         gapm_start_advertise_cmd as GapmStartAdvertiseCmd,
         gapm_start_connection_cmd as GapmStartConnectionCmd,
+        gapm_start_scan_cmd as GapmStartScanCmd,
+        gapm_update_advertise_data_cmd as GapmUpdateAdvertiseDataCmd,
+        gapm_write_att_perm_GAPM_WRITE_AUTH as GAPM_WRITE_AUTH,
+        gapm_write_att_perm_GAPM_WRITE_DISABLE as GAPM_WRITE_DISABLE,
+        gapm_write_att_perm_GAPM_WRITE_ENABLE as GAPM_WRITE_ENABLE,
+        gapm_write_att_perm_GAPM_WRITE_SECURE as GAPM_WRITE_SECURE,
+        gapm_write_att_perm_GAPM_WRITE_UNAUTH as GAPM_WRITE_UNAUTH,
     };
 
-    use crate::platform::core_modules::ke::msg::kernel_msg_type;
+    use crate::{
+        ble_stack::host::gap::GapBDAddr,
+        platform::core_modules::ke::msg::{field_offset, kernel_msg_type, KeMsgDynParams},
+    };
 
     kernel_msg_type!(GapmProfileTaskAdd, GAPM_PROFILE_TASK_ADD_CMD);
     kernel_msg_type!(GapmStartAdvertiseCmd, GAPM_START_ADVERTISE_CMD);
     kernel_msg_type!(GapmStartConnectionCmd, GAPM_START_CONNECTION_CMD);
     kernel_msg_type!(GapmSetDevConfigCmd, GAPM_SET_DEV_CONFIG_CMD);
+    kernel_msg_type!(GapmUpdateAdvertiseDataCmd, GAPM_UPDATE_ADVERTISE_DATA_CMD);
+    kernel_msg_type!(GapmStartScanCmd, GAPM_START_SCAN_CMD);
+    kernel_msg_type!(GapmAdvReportInd, GAPM_ADV_REPORT_IND);
+    kernel_msg_type!(GapmCancelCmd, GAPM_CANCEL_CMD);
+    kernel_msg_type!(GapmPeerNameInd, GAPM_PEER_NAME_IND);
+    kernel_msg_type!(GapmResetCmd, GAPM_RESET_CMD);
+
+    impl KeMsgDynParams for GapmProfileTaskAdd {
+        type Item = u8;
+        fn tail_offset() -> usize {
+            field_offset!(GapmProfileTaskAdd, param)
+        }
+    }
+
+    impl KeMsgDynParams for GapmStartConnectionCmd {
+        type Item = GapBDAddr;
+        fn tail_offset() -> usize {
+            field_offset!(GapmStartConnectionCmd, peers)
+        }
+    }
+
+    impl KeMsgDynParams for GapmPeerNameInd {
+        type Item = u8;
+        fn tail_offset() -> usize {
+            field_offset!(GapmPeerNameInd, name)
+        }
+    }
 }
@@ -1,9 +1,18 @@
 pub use crate::bindings::{
     gap_ad_type as GapAdType, gap_ad_type_GAP_AD_TYPE_COMPLETE_NAME as GAP_AD_TYPE_COMPLETE_NAME,
     gap_ad_type_GAP_AD_TYPE_MANU_SPECIFIC_DATA as GAP_AD_TYPE_MANU_SPECIFIC_DATA,
+    gap_adv_mode_GAP_BROADCASTER_MODE as GAP_BROADCASTER_MODE,
     gap_adv_mode_GAP_GEN_DISCOVERABLE as GAP_GEN_DISCOVERABLE,
+    gap_adv_mode_GAP_LIM_DISCOVERABLE as GAP_LIM_DISCOVERABLE,
     gap_adv_mode_GAP_NON_DISCOVERABLE as GAP_NON_DISCOVERABLE, gap_bdaddr as GapBDAddr,
-    gap_role_GAP_ROLE_PERIPHERAL as GAP_ROLE_PERIPHERAL, GAP_INVALID_CONIDX, GAP_MAX_NAME_SIZE,
+    gap_lk_sec_lvl_GAP_LK_AUTH as GAP_LK_AUTH, gap_lk_sec_lvl_GAP_LK_NO_SEC as GAP_LK_NO_SEC,
+    gap_lk_sec_lvl_GAP_LK_SEC_CON as GAP_LK_SEC_CON, gap_lk_sec_lvl_GAP_LK_UNAUTH as GAP_LK_UNAUTH,
+    gap_role_GAP_ROLE_ALL as GAP_ROLE_ALL, gap_role_GAP_ROLE_BROADCASTER as GAP_ROLE_BROADCASTER,
+    gap_role_GAP_ROLE_CENTRAL as GAP_ROLE_CENTRAL, gap_role_GAP_ROLE_OBSERVER as GAP_ROLE_OBSERVER,
+    gap_role_GAP_ROLE_PERIPHERAL as GAP_ROLE_PERIPHERAL,
+    gap_scan_mode_GAP_GEN_DISCOVERY as GAP_GEN_DISCOVERY,
+    gap_scan_mode_GAP_LIM_DISCOVERY as GAP_LIM_DISCOVERY,
+    gap_scan_mode_GAP_OBSERVER_MODE as GAP_OBSERVER_MODE, GAP_INVALID_CONIDX, GAP_MAX_NAME_SIZE,
 };
 
 pub mod gapc;
@@ -0,0 +1,21 @@
+/// MTU negotiated on the connection `conidx`
+#[inline]
+pub fn gattc_get_mtu(conidx: u8) -> u16 {
+    unsafe { crate::bindings::gattc_get_mtu(conidx) }
+}
+
+pub mod task {
+    pub use crate::bindings::{
+        gattc_cmp_evt as GattcCmpEvt, gattc_exc_mtu_cmd as GattcExcMtuCmd,
+        gattc_msg_id as GattcMsgId, gattc_msg_id_GATTC_CMP_EVT as GATTC_CMP_EVT,
+        gattc_msg_id_GATTC_EXC_MTU_CMD as GATTC_EXC_MTU_CMD,
+        gattc_msg_id_GATTC_MTU_CHANGED_IND as GATTC_MTU_CHANGED_IND,
+        gattc_mtu_changed_ind as GattcMtuChangedInd,
+        gattc_operation_GATTC_MTU_EXCH as GATTC_MTU_EXCH,
+    };
+    use crate::platform::core_modules::ke::msg::kernel_msg_type;
+
+    kernel_msg_type!(GattcExcMtuCmd, GATTC_EXC_MTU_CMD);
+    kernel_msg_type!(GattcMtuChangedInd, GATTC_MTU_CHANGED_IND);
+    kernel_msg_type!(GattcCmpEvt, GATTC_CMP_EVT);
+}
@@ -1,2 +1,3 @@
 pub mod att;
 pub mod gap;
+pub mod gatt;
@@ -6,9 +6,23 @@ pub use crate::bindings::{
     CUSTS1_VAL_IND_REQ, CUSTS1_VAL_WRITE_IND,
 };
 
-use crate::platform::core_modules::ke::msg::kernel_msg_type;
+use crate::platform::core_modules::ke::msg::{field_offset, kernel_msg_type, KeMsgDynParams};
 
 kernel_msg_type!(Custs1ValWriteInd, CUSTS1_VAL_WRITE_IND);
 kernel_msg_type!(Custs1AttInfoRsp, CUSTS1_ATT_INFO_RSP);
 kernel_msg_type!(Custs1ValueReqRsp, CUSTS1_VALUE_REQ_RSP);
 kernel_msg_type!(Custs1ValIndReq, CUSTS1_VAL_IND_REQ);
+
+impl KeMsgDynParams for Custs1ValWriteInd {
+    type Item = u8;
+    fn tail_offset() -> usize {
+        field_offset!(Custs1ValWriteInd, value)
+    }
+}
+
+impl KeMsgDynParams for Custs1ValIndReq {
+    type Item = u8;
+    fn tail_offset() -> usize {
+        field_offset!(Custs1ValIndReq, value)
+    }
+}
@@ -1,3 +1,335 @@
 pub mod error {
+    use core::fmt;
+
     pub use crate::bindings::hl_err as HlErr;
+
+    /// Offset of the HCI error codes within `hl_err`
+    const LL_ERR_OFFSET: u8 = 0x90;
+
+    macro_rules! hci_errors {
+        ($($Variant:ident = $code:literal, $description:literal;)*) => {
+            /// HCI error code, e.g. the reason of a disconnection.
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub enum HciError {
+                $(
+                    #[doc = $description]
+                    $Variant,
+                )*
+                /// Code which is reserved or unknown
+                Other(u8),
+            }
+
+            impl HciError {
+                pub const fn code(self) -> u8 {
+                    match self {
+                        $(HciError::$Variant => $code,)*
+                        HciError::Other(code) => code,
+                    }
+                }
+
+                const fn description(self) -> Option<&'static str> {
+                    match self {
+                        $(HciError::$Variant => Some($description),)*
+                        HciError::Other(_) => None,
+                    }
+                }
+            }
+
+            impl From<u8> for HciError {
+                fn from(code: u8) -> Self {
+                    match code {
+                        $($code => HciError::$Variant,)*
+                        code => HciError::Other(code),
+                    }
+                }
+            }
+        };
+    }
+
+    hci_errors! {
+        UnknownHciCommand = 0x01, "Unknown HCI command";
+        UnknownConnectionId = 0x02, "Unknown connection identifier";
+        HardwareFailure = 0x03, "Hardware failure";
+        PageTimeout = 0x04, "Page timeout";
+        AuthenticationFailure = 0x05, "Authentication failure";
+        PinOrKeyMissing = 0x06, "PIN or key missing";
+        MemoryCapacityExceeded = 0x07, "Memory capacity exceeded";
+        ConnectionTimeout = 0x08, "Connection timeout";
+        ConnectionLimitExceeded = 0x09, "Connection limit exceeded";
+        SynchronousConnectionLimitExceeded = 0x0a, "Synchronous connection limit exceeded";
+        ConnectionAlreadyExists = 0x0b, "Connection already exists";
+        CommandDisallowed = 0x0c, "Command disallowed";
+        RejectedLimitedResources = 0x0d, "Connection rejected due to limited resources";
+        RejectedSecurityReasons = 0x0e, "Connection rejected due to security reasons";
+        RejectedUnacceptableBdAddr = 0x0f, "Connection rejected due to unacceptable BD_ADDR";
+        ConnectionAcceptTimeoutExceeded = 0x10, "Connection accept timeout exceeded";
+        UnsupportedFeatureOrParamValue = 0x11, "Unsupported feature or parameter value";
+        InvalidHciCommandParams = 0x12, "Invalid HCI command parameters";
+        RemoteUserTerminatedConnection = 0x13, "Remote user terminated connection";
+        RemoteDeviceTerminatedLowResources = 0x14,
+            "Remote device terminated connection due to low resources";
+        RemoteDeviceTerminatedPowerOff = 0x15, "Remote device terminated connection due to power off";
+        ConnectionTerminatedByLocalHost = 0x16, "Connection terminated by local host";
+        RepeatedAttempts = 0x17, "Repeated attempts";
+        PairingNotAllowed = 0x18, "Pairing not allowed";
+        UnknownLmpPdu = 0x19, "Unknown LMP PDU";
+        UnsupportedRemoteFeature = 0x1a, "Unsupported remote feature";
+        ScoOffsetRejected = 0x1b, "SCO offset rejected";
+        ScoIntervalRejected = 0x1c, "SCO interval rejected";
+        ScoAirModeRejected = 0x1d, "SCO air mode rejected";
+        InvalidLmpParams = 0x1e, "Invalid LMP or LL parameters";
+        UnspecifiedError = 0x1f, "Unspecified error";
+        UnsupportedLmpParamValue = 0x20, "Unsupported LMP or LL parameter value";
+        RoleChangeNotAllowed = 0x21, "Role change not allowed";
+        LmpResponseTimeout = 0x22, "LMP or LL response timeout";
+        LmpErrorTransactionCollision = 0x23, "LMP or LL error transaction collision";
+        LmpPduNotAllowed = 0x24, "LMP PDU not allowed";
+        EncryptionModeNotAcceptable = 0x25, "Encryption mode not acceptable";
+        LinkKeyCannotBeChanged = 0x26, "Link key cannot be changed";
+        RequestedQosNotSupported = 0x27, "Requested QoS not supported";
+        InstantPassed = 0x28, "Instant passed";
+        PairingWithUnitKeyNotSupported = 0x29, "Pairing with unit key not supported";
+        DifferentTransactionCollision = 0x2a, "Different transaction collision";
+        QosUnacceptableParam = 0x2c, "QoS unacceptable parameter";
+        QosRejected = 0x2d, "QoS rejected";
+        ChannelClassificationNotSupported = 0x2e, "Channel classification not supported";
+        InsufficientSecurity = 0x2f, "Insufficient security";
+        ParamOutOfMandatoryRange = 0x30, "Parameter out of mandatory range";
+        RoleSwitchPending = 0x32, "Role switch pending";
+        ReservedSlotViolation = 0x34, "Reserved slot violation";
+        RoleSwitchFailed = 0x35, "Role switch failed";
+        ExtendedInquiryResponseTooLarge = 0x36, "Extended inquiry response too large";
+        SimplePairingNotSupportedByHost = 0x37, "Secure simple pairing not supported by host";
+        HostBusyPairing = 0x38, "Host busy - pairing";
+        RejectedNoSuitableChannel = 0x39, "Connection rejected due to no suitable channel found";
+        ControllerBusy = 0x3a, "Controller busy";
+        UnacceptableConnectionParams = 0x3b, "Unacceptable connection parameters";
+        AdvertisingTimeout = 0x3c, "Advertising timeout";
+        ConnectionTerminatedMicFailure = 0x3d, "Connection terminated due to MIC failure";
+        ConnectionFailedToBeEstablished = 0x3e, "Connection failed to be established";
+        MacConnectionFailed = 0x3f, "MAC connection failed";
+    }
+
+    impl HciError {
+        pub fn is_timeout(self) -> bool {
+            matches!(
+                self,
+                HciError::PageTimeout
+                    | HciError::ConnectionTimeout
+                    | HciError::ConnectionAcceptTimeoutExceeded
+                    | HciError::LmpResponseTimeout
+                    | HciError::AdvertisingTimeout
+            )
+        }
+
+        pub fn is_remote_user_terminated(self) -> bool {
+            self == HciError::RemoteUserTerminatedConnection
+        }
+
+        /// The peer terminated the connection, for whatever reason
+        pub fn is_remote_terminated(self) -> bool {
+            matches!(
+                self,
+                HciError::RemoteUserTerminatedConnection
+                    | HciError::RemoteDeviceTerminatedLowResources
+                    | HciError::RemoteDeviceTerminatedPowerOff
+            )
+        }
+
+        pub fn is_local_host_terminated(self) -> bool {
+            self == HciError::ConnectionTerminatedByLocalHost
+        }
+    }
+
+    impl fmt::Display for HciError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self.description() {
+                Some(description) => f.write_str(description),
+                None => write!(f, "HCI error {:#04x}", self.code()),
+            }
+        }
+    }
+
+    /// Errors of the GAP, codes `0x40` to `0x4f` of `hl_err`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum GapError {
+        InvalidParam,
+        ProtocolProblem,
+        NotSupported,
+        CommandDisallowed,
+        Canceled,
+        Timeout,
+        Disconnected,
+        NotFound,
+        Rejected,
+        PrivacyConfigProblem,
+        AdvDataInvalid,
+        InsufficientResources,
+        Unexpected,
+        Mismatch,
+        Other(u8),
+    }
+
+    impl GapError {
+        pub const fn code(self) -> u8 {
+            match self {
+                GapError::InvalidParam => HlErr::GAP_ERR_INVALID_PARAM as u8,
+                GapError::ProtocolProblem => HlErr::GAP_ERR_PROTOCOL_PROBLEM as u8,
+                GapError::NotSupported => HlErr::GAP_ERR_NOT_SUPPORTED as u8,
+                GapError::CommandDisallowed => HlErr::GAP_ERR_COMMAND_DISALLOWED as u8,
+                GapError::Canceled => HlErr::GAP_ERR_CANCELED as u8,
+                GapError::Timeout => HlErr::GAP_ERR_TIMEOUT as u8,
+                GapError::Disconnected => HlErr::GAP_ERR_DISCONNECTED as u8,
+                GapError::NotFound => HlErr::GAP_ERR_NOT_FOUND as u8,
+                GapError::Rejected => HlErr::GAP_ERR_REJECTED as u8,
+                GapError::PrivacyConfigProblem => HlErr::GAP_ERR_PRIVACY_CFG_PB as u8,
+                GapError::AdvDataInvalid => HlErr::GAP_ERR_ADV_DATA_INVALID as u8,
+                GapError::InsufficientResources => HlErr::GAP_ERR_INSUFF_RESOURCES as u8,
+                GapError::Unexpected => HlErr::GAP_ERR_UNEXPECTED as u8,
+                GapError::Mismatch => HlErr::GAP_ERR_MISMATCH as u8,
+                GapError::Other(code) => code,
+            }
+        }
+    }
+
+    impl From<u8> for GapError {
+        fn from(code: u8) -> Self {
+            [
+                GapError::InvalidParam,
+                GapError::ProtocolProblem,
+                GapError::NotSupported,
+                GapError::CommandDisallowed,
+                GapError::Canceled,
+                GapError::Timeout,
+                GapError::Disconnected,
+                GapError::NotFound,
+                GapError::Rejected,
+                GapError::PrivacyConfigProblem,
+                GapError::AdvDataInvalid,
+                GapError::InsufficientResources,
+                GapError::Unexpected,
+                GapError::Mismatch,
+            ]
+            .into_iter()
+            .find(|error| error.code() == code)
+            .unwrap_or(GapError::Other(code))
+        }
+    }
+
+    impl fmt::Display for GapError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                GapError::InvalidParam => f.write_str("invalid parameter"),
+                GapError::ProtocolProblem => f.write_str("protocol problem"),
+                GapError::NotSupported => f.write_str("not supported"),
+                GapError::CommandDisallowed => f.write_str("command disallowed"),
+                GapError::Canceled => f.write_str("canceled"),
+                GapError::Timeout => f.write_str("timeout"),
+                GapError::Disconnected => f.write_str("disconnected"),
+                GapError::NotFound => f.write_str("not found"),
+                GapError::Rejected => f.write_str("rejected"),
+                GapError::PrivacyConfigProblem => f.write_str("privacy configuration problem"),
+                GapError::AdvDataInvalid => f.write_str("invalid advertising data"),
+                GapError::InsufficientResources => f.write_str("insufficient resources"),
+                GapError::Unexpected => f.write_str("unexpected"),
+                GapError::Mismatch => f.write_str("mismatch"),
+                GapError::Other(code) => write!(f, "{:#04x}", code),
+            }
+        }
+    }
+
+    /// Error status of the host stack (`hl_err`), e.g. of a command complete event.
+    ///
+    /// Codes are classified by the ranges of the layers in `hl_err`, only the GAP and the link
+    /// layer errors are decoded further.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum HlError {
+        Att(u8),
+        L2c(u8),
+        Gap(GapError),
+        Gatt(u8),
+        Smp(u8),
+        Prf(u8),
+        /// Error of the link layer
+        Hci(HciError),
+        Unknown(u8),
+    }
+
+    pub type HlResult<T = ()> = Result<T, HlError>;
+
+    impl HlError {
+        /// Converts a status, `GAP_ERR_NO_ERROR` is `Ok`.
+        pub fn from_status(status: u8) -> HlResult {
+            if status == HlErr::GAP_ERR_NO_ERROR as u8 {
+                Ok(())
+            } else {
+                Err(HlError::from(status))
+            }
+        }
+
+        pub fn code(self) -> u8 {
+            match self {
+                HlError::Att(code)
+                | HlError::L2c(code)
+                | HlError::Gatt(code)
+                | HlError::Smp(code)
+                | HlError::Prf(code)
+                | HlError::Unknown(code) => code,
+                HlError::Gap(error) => error.code(),
+                HlError::Hci(error) => error.code().wrapping_add(LL_ERR_OFFSET),
+            }
+        }
+
+        pub fn is_timeout(self) -> bool {
+            match self {
+                HlError::Gap(error) => error == GapError::Timeout,
+                HlError::Hci(error) => error.is_timeout(),
+                _ => false,
+            }
+        }
+
+        pub fn is_remote_user_terminated(self) -> bool {
+            matches!(self, HlError::Hci(error) if error.is_remote_user_terminated())
+        }
+
+        pub fn is_canceled(self) -> bool {
+            self == HlError::Gap(GapError::Canceled)
+        }
+    }
+
+    impl From<u8> for HlError {
+        fn from(code: u8) -> Self {
+            match code {
+                0x01..=0x2f => HlError::Att(code),
+                0x30..=0x3f => HlError::L2c(code),
+                0x40..=0x4f => HlError::Gap(GapError::from(code)),
+                0x50..=0x5f => HlError::Gatt(code),
+                0x61..=0x6f | 0xd0..=0xdf => HlError::Smp(code),
+                0x80..=0x90 => HlError::Prf(code),
+                0x91..=0xcf => HlError::Hci(HciError::from(code - LL_ERR_OFFSET)),
+                _ => HlError::Unknown(code),
+            }
+        }
+    }
+
+    impl From<HciError> for HlError {
+        fn from(error: HciError) -> Self {
+            HlError::Hci(error)
+        }
+    }
+
+    impl fmt::Display for HlError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                HlError::Att(code) => write!(f, "ATT error {:#04x}", code),
+                HlError::L2c(code) => write!(f, "L2CAP error {:#04x}", code),
+                HlError::Gap(error) => write!(f, "GAP error: {}", error),
+                HlError::Gatt(code) => write!(f, "GATT error {:#04x}", code),
+                HlError::Smp(code) => write!(f, "SMP error {:#04x}", code),
+                HlError::Prf(code) => write!(f, "profile error {:#04x}", code),
+                HlError::Hci(error) => write!(f, "link layer error: {}", error),
+                HlError::Unknown(code) => write!(f, "unknown error {:#04x}", code),
+            }
+        }
+    }
 }
@@ -0,0 +1,178 @@
+//! Awaitable connection events, signaled by
+//! [`default_app_on_connection`](crate::app_modules::default_app_on_connection) and
+//! [`default_app_on_disconnect`](crate::app_modules::default_app_on_disconnect), and awaitable
+//! versions of the queries of [`peer_info`] and [`link`], which fail with a timeout if the peer
+//! does not answer them.
+
+use core::{
+    cell::{Cell, RefCell},
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use alloc::{boxed::Box, rc::Rc, vec::Vec};
+
+use crate::{
+    app_modules::app_common::app::{
+        link::{self, DataLength, LinkConfigResult},
+        peer_info::{
+            self, ChannelMap, PeerFeatures, PeerVersion, QueryCallback, QueryResult, Rssi,
+        },
+    },
+    ble_stack::{
+        host::gap::gapc::{gapc_get_conidx, task::GapcDisconnectInd},
+        rwble_hl::error::{HciError, HlResult},
+    },
+};
+
+use super::Signal;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Disconnection {
+    pub conidx: u8,
+    pub reason: HciError,
+}
+
+struct Event<T> {
+    /// Incremented on every occurrence of the event
+    seq: Cell<u32>,
+    value: Cell<Option<T>>,
+    wakers: RefCell<Vec<Waker>>,
+}
+
+// Events are only accessed from the main loop
+unsafe impl<T> Sync for Event<T> {}
+
+impl<T> Event<T> {
+    const fn new() -> Self {
+        Self {
+            seq: Cell::new(0),
+            value: Cell::new(None),
+            wakers: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl<T: Copy> Event<T> {
+    fn signal(&self, value: T) {
+        self.seq.set(self.seq.get().wrapping_add(1));
+        self.value.set(Some(value));
+
+        let wakers = core::mem::take(&mut *self.wakers.borrow_mut());
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    fn next(&'static self) -> NextEvent<T> {
+        NextEvent {
+            event: self,
+            seq: self.seq.get(),
+        }
+    }
+}
+
+/// Future which completes on the next occurrence of an event after its creation.
+pub struct NextEvent<T: 'static> {
+    event: &'static Event<T>,
+    seq: u32,
+}
+
+impl<T: Copy> Future for NextEvent<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<T> {
+        if self.event.seq.get() != self.seq {
+            // Several events in between are collapsed into the most recent one
+            return Poll::Ready(self.event.value.get().unwrap());
+        }
+
+        let mut wakers = self.event.wakers.borrow_mut();
+        if !wakers.iter().any(|waker| waker.will_wake(context.waker())) {
+            wakers.push(context.waker().clone());
+        }
+
+        Poll::Pending
+    }
+}
+
+static CONNECTION: Event<u8> = Event::new();
+static DISCONNECTION: Event<Disconnection> = Event::new();
+
+/// Completes with the connection index of the next established connection.
+pub fn next_connection() -> NextEvent<u8> {
+    CONNECTION.next()
+}
+
+/// Completes on the next disconnection.
+pub fn next_disconnection() -> NextEvent<Disconnection> {
+    DISCONNECTION.next()
+}
+
+pub(crate) fn signal_connection(conidx: u8) {
+    CONNECTION.signal(conidx);
+}
+
+pub(crate) fn signal_disconnection(param: &GapcDisconnectInd) {
+    DISCONNECTION.signal(Disconnection {
+        conidx: gapc_get_conidx(param.conhdl),
+        reason: HciError::from(param.reason),
+    });
+}
+
+/// Future which completes with the result of a query.
+pub struct Query<T> {
+    signal: Rc<RefCell<Signal<HlResult<T>>>>,
+}
+
+impl<T: 'static> Query<T> {
+    fn start<E>(query: impl FnOnce(QueryCallback<T>) -> Result<(), E>) -> Result<Self, E> {
+        let signal = Rc::new(RefCell::new(Signal::new()));
+        let callback_signal = signal.clone();
+
+        query(Box::new(move |result| {
+            callback_signal.borrow_mut().complete(result)
+        }))?;
+
+        Ok(Self { signal })
+    }
+}
+
+impl<T> Future for Query<T> {
+    type Output = HlResult<T>;
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<HlResult<T>> {
+        self.signal.borrow_mut().poll(context)
+    }
+}
+
+pub fn peer_features(conidx: u8) -> QueryResult<Query<PeerFeatures>> {
+    Query::start(|callback| peer_info::app_easy_gap_get_peer_features(conidx, callback))
+}
+
+pub fn peer_version(conidx: u8) -> QueryResult<Query<PeerVersion>> {
+    Query::start(|callback| peer_info::app_easy_gap_get_peer_version(conidx, callback))
+}
+
+pub fn con_rssi(conidx: u8) -> QueryResult<Query<Rssi>> {
+    Query::start(|callback| peer_info::app_easy_gap_get_con_rssi(conidx, callback))
+}
+
+pub fn con_channel_map(conidx: u8) -> QueryResult<Query<ChannelMap>> {
+    Query::start(|callback| peer_info::app_easy_gap_get_con_channel_map(conidx, callback))
+}
+
+pub fn exchange_mtu(conidx: u8) -> QueryResult<Query<u16>> {
+    Query::start(|callback| link::app_easy_gatt_exchange_mtu(conidx, callback))
+}
+
+pub fn set_data_packet_length(
+    conidx: u8,
+    tx_octets: u16,
+    tx_time: u16,
+) -> LinkConfigResult<Query<DataLength>> {
+    Query::start(|callback| {
+        link::app_easy_gap_set_data_packet_length(conidx, tx_octets, tx_time, callback)
+    })
+}
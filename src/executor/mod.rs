@@ -0,0 +1,142 @@
+//! Single-threaded executor for `async` application code.
+//!
+//! Tasks are polled by [`poll`], which has to be called from one of the main loop callbacks in
+//! [`ArchMainLoopCallbacks`](crate::platform::arch::ArchMainLoopCallbacks), e.g.
+//! `app_on_system_powered`. Wakers are fired from kernel message handlers which are executed by
+//! the main loop as well.
+
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::platform::arch::{ArchMainLoopCallbackRet, GOTO_SLEEP, KEEP_POWERED};
+
+pub mod gap;
+pub mod timer;
+
+struct Task {
+    id: usize,
+    /// `None` while the task is polled
+    future: Option<Pin<Box<dyn Future<Output = ()>>>>,
+    woken: bool,
+}
+
+#[link_section = "retention_mem_area0"]
+static mut TASKS: Vec<Task> = Vec::new();
+
+#[link_section = "retention_mem_area0"]
+static mut NEXT_TASK_ID: usize = 0;
+
+static WAKER_VTABLE: RawWakerVTable =
+    RawWakerVTable::new(waker_clone, waker_wake, waker_wake, waker_drop);
+
+unsafe fn waker_clone(data: *const ()) -> RawWaker {
+    RawWaker::new(data, &WAKER_VTABLE)
+}
+
+unsafe fn waker_wake(data: *const ()) {
+    let id = data as usize;
+
+    // Tasks which have already completed are ignored
+    if let Some(task) = TASKS.iter_mut().find(|task| task.id == id) {
+        task.woken = true;
+    }
+}
+
+unsafe fn waker_drop(_data: *const ()) {}
+
+fn task_waker(id: usize) -> Waker {
+    unsafe { Waker::from_raw(RawWaker::new(id as *const (), &WAKER_VTABLE)) }
+}
+
+/// Adds `future` to the executor, it is polled for the first time on the next call to [`poll`].
+pub fn spawn(future: impl Future<Output = ()> + 'static) {
+    unsafe {
+        NEXT_TASK_ID = NEXT_TASK_ID.wrapping_add(1);
+
+        TASKS.push(Task {
+            id: NEXT_TASK_ID,
+            future: Some(Box::pin(future)),
+            woken: true,
+        });
+    }
+}
+
+/// Polls all woken tasks.
+///
+/// Returns `KEEP_POWERED` if a task has been woken in the meantime and the executor needs to be
+/// polled again, `GOTO_SLEEP` otherwise.
+pub fn poll() -> ArchMainLoopCallbackRet {
+    // Tasks spawned while polling are appended and polled within the same call
+    let mut idx = 0;
+    while idx < unsafe { TASKS.len() } {
+        let task = unsafe { &mut TASKS[idx] };
+
+        if task.woken {
+            task.woken = false;
+
+            let id = task.id;
+            let mut future = task.future.take().unwrap();
+
+            let waker = task_waker(id);
+            let mut context = Context::from_waker(&waker);
+
+            let result = future.as_mut().poll(&mut context);
+
+            // The task list might have been modified while polling
+            let task = unsafe { &mut TASKS[idx] };
+            if result.is_pending() {
+                task.future = Some(future);
+            }
+        }
+
+        idx += 1;
+    }
+
+    unsafe {
+        TASKS.retain(|task| task.future.is_some());
+
+        if TASKS.iter().any(|task| task.woken) {
+            KEEP_POWERED
+        } else {
+            GOTO_SLEEP
+        }
+    }
+}
+
+/// Shared state of a future which is completed from a callback.
+pub(crate) struct Signal<T> {
+    value: Option<T>,
+    waker: Option<Waker>,
+}
+
+impl<T> Signal<T> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            value: None,
+            waker: None,
+        }
+    }
+
+    pub(crate) fn complete(&mut self, value: T) {
+        self.value = Some(value);
+
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+
+    pub(crate) fn poll(&mut self, context: &mut Context<'_>) -> Poll<T> {
+        match self.value.take() {
+            Some(value) => Poll::Ready(value),
+            None => {
+                self.waker = Some(context.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
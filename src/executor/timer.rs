@@ -0,0 +1,63 @@
+use core::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use alloc::{boxed::Box, rc::Rc};
+
+use crate::app_modules::{timer::AppTimerResult, timer_queue::QueuedTimer};
+
+use super::Signal;
+
+/// Future which completes after a delay, the timer is canceled when it is dropped.
+pub struct Timer {
+    signal: Rc<RefCell<Signal<()>>>,
+    timer: Option<QueuedTimer>,
+}
+
+impl Timer {
+    /// Completes after at least `ms` milliseconds, rounded up to the 10 ms kernel timer resolution.
+    ///
+    /// Fails if the delay exceeds the kernel timer range or no slot of the timer module is free.
+    pub fn after(ms: u32) -> AppTimerResult<Self> {
+        let delay = ms / 10 + (ms % 10 != 0) as u32;
+
+        let signal = Rc::new(RefCell::new(Signal::new()));
+        let callback_signal = signal.clone();
+
+        let timer = QueuedTimer::new(
+            delay,
+            Box::new(move || callback_signal.borrow_mut().complete(())),
+        )?;
+
+        Ok(Self {
+            signal,
+            timer: Some(timer),
+        })
+    }
+}
+
+impl Future for Timer {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+        let result = self.signal.borrow_mut().poll(context);
+
+        if result.is_ready() {
+            self.timer = None;
+        }
+
+        result
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            // The timer might have expired without being polled since
+            let _ = timer.cancel();
+        }
+    }
+}
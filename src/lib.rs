@@ -15,6 +15,7 @@ mod bindings;
 pub mod app;
 pub mod app_modules;
 pub mod ble_stack;
+pub mod executor;
 pub mod platform;
 pub mod stdlib;
 pub mod allocator;
@@ -1,5 +1,7 @@
 pub use crate::bindings::{
     arch_main_loop_callback_ret_t as ArchMainLoopCallbackRet,
+    arch_main_loop_callback_ret_t_GOTO_SLEEP as GOTO_SLEEP,
+    arch_main_loop_callback_ret_t_KEEP_POWERED as KEEP_POWERED,
     arch_main_loop_callbacks as ArchMainLoopCallbacks, sleep_mode_t as SleepMode,
 };
 pub use da14531_sdk_macros::register_main_loop_callbacks;
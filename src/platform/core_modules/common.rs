@@ -5,7 +5,11 @@ pub use crate::bindings::{
     adv_channel_map_ADV_CHNL_39_EN as ADV_CHNL_39_EN,
     adv_filter_policy_ADV_ALLOW_SCAN_ANY_CON_ANY as ADV_ALLOW_SCAN_ANY_CON_ANY,
     adv_filter_policy_ADV_ALLOW_SCAN_ANY_CON_WLST as ADV_ALLOW_SCAN_ANY_CON_WLST,
-    bd_addr as BDAddr, co_list as CoList, co_list_hdr as CoListHdr, ADV_DATA_LEN, KEY_LEN,
+    adv_report as AdvReport, bd_addr as BDAddr, co_list as CoList, co_list_hdr as CoListHdr,
+    scan_dup_filter_policy_SCAN_FILT_DUPLIC_DIS as SCAN_FILT_DUPLIC_DIS,
+    scan_dup_filter_policy_SCAN_FILT_DUPLIC_EN as SCAN_FILT_DUPLIC_EN,
+    scan_filter_policy_SCAN_ALLOW_ADV_ALL as SCAN_ALLOW_ADV_ALL,
+    scan_filter_policy_SCAN_ALLOW_ADV_WLST as SCAN_ALLOW_ADV_WLST, ADV_DATA_LEN, KEY_LEN,
     SCAN_RSP_DATA_LEN, hci_le_tx_test_cmd as HciLeTxTestCmd, hci_le_rx_test_cmd as HciLeRxTestCmd,
 };
 
@@ -1,11 +1,34 @@
 pub use crate::bindings::{
     ke_msg_handler as KeMsgHandler, ke_msg_id_t as KeMsgId, ke_msg_status_tag as KeMsgStatusTag,
     ke_msg_status_tag_KE_MSG_CONSUMED as KE_MSG_CONSUMED,
+    ke_msg_status_tag_KE_MSG_NO_FREE as KE_MSG_NO_FREE,
 };
 
-use crate::bindings::{ke_msg_alloc, ke_msg_send};
+use core::{marker::PhantomData, ops::Deref};
 
-pub struct KernelMessage<const ID: u32, const SIZE: u16, T>(*mut T);
+use crate::bindings::{
+    ke_msg, ke_msg_alloc, ke_msg_forward, ke_msg_forward_new_id, ke_msg_free, ke_msg_send,
+};
+
+use super::task::KeTaskId;
+
+/// Header of the kernel message which carries the parameters at `param`
+#[inline]
+unsafe fn ke_param2msg<T>(param: *const T) -> *const ke_msg {
+    (param as *const u8).sub(field_offset!(ke_msg, param)) as *const ke_msg
+}
+
+/// Parameters ending in a variable length array, e.g. `GapmStartConnectionCmd::peers`.
+pub trait KeMsgDynParams {
+    type Item;
+    /// Offset of the variable length array, see [`field_offset`]
+    fn tail_offset() -> usize;
+}
+
+/// Outgoing kernel message, it is freed when dropped without being sent.
+///
+/// The second field is the number of bytes allocated in addition to `T`.
+pub struct KernelMessage<const ID: u32, const SIZE: u16, T>(*mut T, u16);
 
 impl<const ID: u32, const SIZE: u16, T> KernelMessage<ID, SIZE, T> {
     pub fn new(src_id: KeMsgId, dest_id: KeMsgId) -> Self {
@@ -18,7 +41,7 @@ impl<const ID: u32, const SIZE: u16, T> KernelMessage<ID, SIZE, T> {
             ) as *mut T
         };
 
-        Self(msg_ptr)
+        Self(msg_ptr, SIZE)
     }
 
     pub fn new_dynamic(size: u16, src_id: KeMsgId, dest_id: KeMsgId) -> Self {
@@ -31,7 +54,7 @@ impl<const ID: u32, const SIZE: u16, T> KernelMessage<ID, SIZE, T> {
             ) as *mut T
         };
 
-        Self(msg_ptr)
+        Self(msg_ptr, size)
     }
 
     pub fn fields(&mut self) -> &mut T {
@@ -42,9 +65,149 @@ impl<const ID: u32, const SIZE: u16, T> KernelMessage<ID, SIZE, T> {
         unsafe {
             ke_msg_send(self.0 as *const cty::c_void);
         }
+
+        // The kernel owns the message now
+        core::mem::forget(self);
+    }
+}
+
+impl<const ID: u32, const SIZE: u16, T: KeMsgDynParams> KernelMessage<ID, SIZE, T> {
+    /// Variable length array sized from the allocated length, zero initialized by `ke_msg_alloc`.
+    pub fn tail(&mut self) -> &mut [T::Item] {
+        let len = tail_len::<T>(core::mem::size_of::<T>() + self.1 as usize);
+
+        unsafe {
+            core::slice::from_raw_parts_mut(
+                (self.0 as *mut u8).add(T::tail_offset()) as *mut T::Item,
+                len,
+            )
+        }
+    }
+
+    /// Writes `value` to the start of the variable length array, e.g. the database configuration
+    /// of a profile following `GapmProfileTaskAdd`.
+    ///
+    /// Panics if `value` does not fit into the allocated array.
+    pub fn write_tail<U: Copy>(&mut self, value: U) {
+        let tail = self.tail();
+
+        assert!(core::mem::size_of::<U>() <= core::mem::size_of_val(tail));
+
+        unsafe {
+            (tail.as_mut_ptr() as *mut U).write_unaligned(value);
+        }
+    }
+}
+
+/// Number of array items fitting in `param_len` bytes of parameters
+#[inline]
+fn tail_len<T: KeMsgDynParams>(param_len: usize) -> usize {
+    param_len.saturating_sub(T::tail_offset()) / core::mem::size_of::<T::Item>()
+}
+
+impl<const ID: u32, const SIZE: u16, T> Drop for KernelMessage<ID, SIZE, T> {
+    fn drop(&mut self) {
+        unsafe {
+            ke_msg_free(ke_param2msg(self.0));
+        }
+    }
+}
+
+/// Incoming kernel message passed to a message handler, it is owned by the kernel.
+pub struct ReceivedMessage<'a, T> {
+    param: *const T,
+    _param: PhantomData<&'a T>,
+}
+
+impl<'a, T> ReceivedMessage<'a, T> {
+    /// # Safety
+    ///
+    /// `param` must point to the parameters of a kernel message of type `T` which stays valid
+    /// for `'a`.
+    pub unsafe fn from_raw(param: *const cty::c_void) -> Self {
+        Self {
+            param: param as *const T,
+            _param: PhantomData,
+        }
+    }
+
+    fn header(&self) -> &ke_msg {
+        unsafe { &*ke_param2msg(self.param) }
+    }
+
+    pub fn id(&self) -> KeMsgId {
+        self.header().id
+    }
+
+    pub fn dest_id(&self) -> KeTaskId {
+        self.header().dest_id
+    }
+
+    pub fn src_id(&self) -> KeTaskId {
+        self.header().src_id
+    }
+
+    /// Length of the parameters including any variable length tail
+    pub fn param_len(&self) -> u16 {
+        self.header().param_len
+    }
+
+    /// Forwards the message to `dest_id`, the handler has to return the returned status so that
+    /// the kernel does not free the message.
+    pub fn forward(self, dest_id: KeTaskId, src_id: KeTaskId) -> KeMsgStatusTag {
+        unsafe {
+            ke_msg_forward(self.param as *const cty::c_void, dest_id, src_id);
+        }
+        KE_MSG_NO_FREE
+    }
+
+    /// Same as [`ReceivedMessage::forward`] but changes the message id to `msg_id`.
+    pub fn forward_new_id(
+        self,
+        msg_id: KeMsgId,
+        dest_id: KeTaskId,
+        src_id: KeTaskId,
+    ) -> KeMsgStatusTag {
+        unsafe {
+            ke_msg_forward_new_id(self.param as *const cty::c_void, msg_id, dest_id, src_id);
+        }
+        KE_MSG_NO_FREE
     }
 }
 
+impl<'a, T: KeMsgDynParams> ReceivedMessage<'a, T> {
+    /// Variable length array sized from the received parameter length.
+    pub fn tail(&self) -> &'a [T::Item] {
+        let len = tail_len::<T>(self.param_len() as usize);
+
+        unsafe {
+            core::slice::from_raw_parts(
+                (self.param as *const u8).add(T::tail_offset()) as *const T::Item,
+                len,
+            )
+        }
+    }
+}
+
+impl<'a, T> Deref for ReceivedMessage<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.param }
+    }
+}
+
+/// Message id and parameter type of the [`KernelMessage`] aliases created by `kernel_msg_type!`
+pub trait KeMsgType {
+    const ID: KeMsgId;
+    type Params;
+}
+
+impl<const ID: u32, const SIZE: u16, T> KeMsgType for KernelMessage<ID, SIZE, T> {
+    const ID: KeMsgId = ID as KeMsgId;
+    type Params = T;
+}
+
 macro_rules! kernel_msg_type {
     ($Msg: ty, $id: ident) => {
         paste::paste! {
@@ -81,3 +244,16 @@ macro_rules! kernel_msg_type {
 }
 
 pub(crate) use kernel_msg_type;
+
+/// Byte offset of `$field` within `$Type`, computed from the layout of an uninitialized value.
+macro_rules! field_offset {
+    ($Type: ty, $field: ident) => {{
+        let value = core::mem::MaybeUninit::<$Type>::uninit();
+        let base = value.as_ptr();
+        #[allow(unused_unsafe)]
+        let field = unsafe { core::ptr::addr_of!((*base).$field) };
+        field as usize - base as usize
+    }};
+}
+
+pub(crate) use field_offset;
@@ -3,12 +3,38 @@ pub use crate::bindings::{
     ke_task_id_t as KeTaskId,
 };
 
+use core::marker::PhantomData;
+
+use alloc::{boxed::Box, vec, vec::Vec};
+
+use super::msg::{
+    KeMsgHandler, KeMsgId, KeMsgStatusTag, KeMsgType, ReceivedMessage, KE_MSG_CONSUMED,
+};
+
 unsafe impl Sync for KeTaskDesc {}
 
+/// Task identifier of the instance `index` of the task `task_type`
+#[inline]
+pub const fn ke_build_id(task_type: u8, index: u8) -> KeTaskId {
+    ((index as KeTaskId) << 8) | task_type as KeTaskId
+}
+
+/// Instance index of a task identifier, e.g. the connection index of a GAPC task
+#[inline]
+pub const fn ke_idx_get(task_id: KeTaskId) -> u8 {
+    (task_id >> 8) as u8
+}
+
+/// Task type of a task identifier
 #[inline]
-pub fn ke_state_set(task_id: KeTaskId, state_id: KeState) {
+pub const fn ke_type_get(task_id: KeTaskId) -> u8 {
+    task_id as u8
+}
+
+#[inline]
+pub fn ke_state_set(task_id: KeTaskId, state_id: impl Into<KeState>) {
     unsafe {
-        crate::bindings::ke_state_set(task_id, state_id);
+        crate::bindings::ke_state_set(task_id, state_id.into());
     }
 }
 
@@ -23,3 +49,248 @@ pub fn ke_task_create(task_type: u8, task_desc: &KeTaskDesc) {
         crate::bindings::ke_task_create(task_type, task_desc);
     }
 }
+
+#[derive(Debug)]
+pub enum KeTaskError {
+    Fail,
+    Unknown,
+    CapaExceeded,
+    AlreadyExists,
+    /// Return code which is not part of `KE_TASK_STATUS`
+    Other(u8),
+}
+
+pub type KeTaskResult<T = ()> = Result<T, KeTaskError>;
+
+fn ret_to_result(ret: u8) -> KeTaskResult {
+    match ret {
+        0 => Ok(()),
+        1 => Err(KeTaskError::Fail),
+        2 => Err(KeTaskError::Unknown),
+        3 => Err(KeTaskError::CapaExceeded),
+        4 => Err(KeTaskError::AlreadyExists),
+        ret => Err(KeTaskError::Other(ret)),
+    }
+}
+
+/// State enum of a kernel task, see [`ke_task_states!`](crate::ke_task_states).
+pub trait KeTaskState: Copy + Into<KeState> + TryFrom<KeState> {
+    /// Number of states
+    const STATE_MAX: u16;
+}
+
+/// Declares a `#[repr(u8)]` state enum implementing [`KeTaskState`].
+///
+/// ```ignore
+/// ke_task_states! {
+///     pub enum MyTaskState {
+///         Idle,
+///         Busy,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! ke_task_states {
+    ($(#[$meta: meta])* $vis: vis enum $Name: ident { $($State: ident),+ $(,)? }) => {
+        $(#[$meta])*
+        #[repr(u8)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        $vis enum $Name {
+            $($State),+
+        }
+
+        impl From<$Name> for $crate::platform::core_modules::ke::task::KeState {
+            fn from(state: $Name) -> Self {
+                state as Self
+            }
+        }
+
+        impl TryFrom<$crate::platform::core_modules::ke::task::KeState> for $Name {
+            type Error = $crate::platform::core_modules::ke::task::KeState;
+
+            fn try_from(state: $crate::platform::core_modules::ke::task::KeState) -> Result<Self, Self::Error> {
+                $(
+                    if state == $Name::$State as $crate::platform::core_modules::ke::task::KeState {
+                        return Ok($Name::$State);
+                    }
+                )+
+                Err(state)
+            }
+        }
+
+        impl $crate::platform::core_modules::ke::task::KeTaskState for $Name {
+            const STATE_MAX: u16 = [$($Name::$State),+].len() as u16;
+        }
+    };
+}
+
+#[inline]
+pub fn ke_state_get_typed<S: KeTaskState>(task_id: KeTaskId) -> Option<S> {
+    S::try_from(ke_state_get(task_id)).ok()
+}
+
+pub type KeTaskMsgHandler<T> = fn(msg: ReceivedMessage<T>) -> KeMsgStatusTag;
+
+type Trampoline = unsafe fn(*const (), *const cty::c_void) -> KeMsgStatusTag;
+
+struct HandlerEntry {
+    msg_id: KeMsgId,
+    /// Type erased `KeTaskMsgHandler<T>`
+    handler: *const (),
+    trampoline: Trampoline,
+}
+
+unsafe fn trampoline<T>(handler: *const (), param: *const cty::c_void) -> KeMsgStatusTag {
+    let handler: KeTaskMsgHandler<T> = core::mem::transmute(handler);
+
+    handler(ReceivedMessage::from_raw(param))
+}
+
+/// Message handlers of a single task state.
+#[derive(Default)]
+pub struct KeStateHandlerBuilder {
+    handlers: Vec<HandlerEntry>,
+}
+
+impl KeStateHandlerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_handler<M: KeMsgType>(mut self, handler: KeTaskMsgHandler<M::Params>) -> Self {
+        self.handlers.push(HandlerEntry {
+            msg_id: M::ID,
+            handler: handler as *const (),
+            trampoline: trampoline::<M::Params>,
+        });
+        self
+    }
+
+    fn find(&self, msg_id: KeMsgId) -> Option<&HandlerEntry> {
+        self.handlers.iter().find(|entry| entry.msg_id == msg_id)
+    }
+
+    /// Creates the kernel message table, all messages are dispatched by [`task_msg_handler`].
+    fn build(&self) -> KeStateHandler {
+        let msg_table: Vec<KeMsgHandler> = self
+            .handlers
+            .iter()
+            .map(|entry| KeMsgHandler {
+                id: entry.msg_id,
+                func: Some(task_msg_handler),
+            })
+            .collect();
+
+        KeStateHandler {
+            msg_cnt: msg_table.len() as u16,
+            msg_table: Box::leak(msg_table.into_boxed_slice()).as_ptr(),
+        }
+    }
+}
+
+struct TaskHandlers {
+    task_type: u8,
+    states: Vec<KeStateHandlerBuilder>,
+    default_handler: KeStateHandlerBuilder,
+}
+
+#[link_section = "retention_mem_area0"]
+static mut TASKS: Vec<TaskHandlers> = Vec::new();
+
+/// Looks up the Rust handler the same way the kernel looks up the message table entry, messages
+/// without a handler are consumed.
+unsafe extern "C" fn task_msg_handler(
+    msg_id: KeMsgId,
+    param: *const cty::c_void,
+    dest_id: KeTaskId,
+    _src_id: KeTaskId,
+) -> cty::c_int {
+    let task_type = (dest_id & 0xff) as u8;
+    let state = ke_state_get(dest_id) as usize;
+
+    let entry = TASKS
+        .iter()
+        .find(|task| task.task_type == task_type)
+        .and_then(|task| {
+            task.states
+                .get(state)
+                .and_then(|handlers| handlers.find(msg_id))
+                .or_else(|| task.default_handler.find(msg_id))
+        });
+
+    match entry {
+        Some(entry) => (entry.trampoline)(entry.handler, param) as cty::c_int,
+        None => KE_MSG_CONSUMED as cty::c_int,
+    }
+}
+
+/// Builds a [`KeTaskDesc`] with message handlers written in Rust and creates the task.
+pub struct KeTaskDescBuilder<S: KeTaskState> {
+    task_type: u8,
+    states: Vec<KeStateHandlerBuilder>,
+    default_handler: KeStateHandlerBuilder,
+    idx_max: u16,
+    _state: PhantomData<S>,
+}
+
+impl<S: KeTaskState> KeTaskDescBuilder<S> {
+    pub fn new(task_type: u8) -> Self {
+        Self {
+            task_type,
+            states: (0..S::STATE_MAX)
+                .map(|_| KeStateHandlerBuilder::new())
+                .collect(),
+            default_handler: KeStateHandlerBuilder::new(),
+            idx_max: 1,
+            _state: PhantomData,
+        }
+    }
+
+    /// Sets the number of task instances
+    pub fn set_idx_max(mut self, idx_max: u16) -> Self {
+        self.idx_max = idx_max;
+        self
+    }
+
+    pub fn set_state_handler(mut self, state: S, handler: KeStateHandlerBuilder) -> Self {
+        let state: KeState = state.into();
+        self.states[state as usize] = handler;
+        self
+    }
+
+    /// Handlers for messages which are not handled in the current state
+    pub fn set_default_handler(mut self, handler: KeStateHandlerBuilder) -> Self {
+        self.default_handler = handler;
+        self
+    }
+
+    /// Creates the task, the tables referenced by the task descriptor are never freed.
+    pub fn create(self) -> KeTaskResult {
+        let state_handler: Vec<KeStateHandler> = self
+            .states
+            .iter()
+            .map(KeStateHandlerBuilder::build)
+            .collect();
+
+        let task_desc = Box::leak(Box::new(KeTaskDesc {
+            state_handler: Box::leak(state_handler.into_boxed_slice()).as_ptr(),
+            default_handler: Box::leak(Box::new(self.default_handler.build())),
+            state: Box::leak(vec![0 as KeState; self.idx_max as usize].into_boxed_slice())
+                .as_mut_ptr(),
+            state_max: S::STATE_MAX,
+            idx_max: self.idx_max,
+        }));
+
+        ret_to_result(unsafe { crate::bindings::ke_task_create(self.task_type, task_desc) })?;
+
+        unsafe {
+            TASKS.push(TaskHandlers {
+                task_type: self.task_type,
+                states: self.states,
+                default_handler: self.default_handler,
+            });
+        }
+
+        Ok(())
+    }
+}
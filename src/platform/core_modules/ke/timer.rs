@@ -13,3 +13,32 @@ pub fn ke_timer_clear(timer_id: KeMsgId, task: KeTaskId) {
         crate::bindings::ke_timer_clear(timer_id, task);
     }
 }
+
+/// Current kernel time in timer units (10 ms)
+#[inline]
+pub fn ke_time() -> u32 {
+    unsafe { crate::bindings::ke_time() }
+}
+
+/// The kernel time wraps at `BLE_GROSSTARGET_MASK`
+pub const KE_TIME_MASK: u32 = 0x007f_ffff;
+
+/// Kernel time `delay` timer units after `time`
+#[inline]
+pub const fn ke_time_add(time: u32, delay: u32) -> u32 {
+    time.wrapping_add(delay) & KE_TIME_MASK
+}
+
+/// Timer units from `time` to `target`, negative if `target` is in the past of `time`.
+///
+/// Both times must be less than half of the kernel time range apart, as for `ke_time_past`.
+#[inline]
+pub const fn ke_time_diff(target: u32, time: u32) -> i32 {
+    let diff = target.wrapping_sub(time) & KE_TIME_MASK;
+
+    if diff > KE_TIME_MASK / 2 {
+        diff as i32 - (KE_TIME_MASK as i32 + 1)
+    } else {
+        diff as i32
+    }
+}
@@ -36,6 +36,7 @@ pub use crate::bindings::{
     KE_API_ID_TASK_ID_WSSS as TASK_ID_WSSS, KE_MEM_ATT_DB, KE_MEM_ENV, KE_MEM_KE_MSG,
     KE_MEM_NON_RETENTION, KE_TASK_TYPE as KeTaskType, KE_TASK_TYPE_TASK_APP as TASK_APP,
     KE_TASK_TYPE_TASK_GAPC as TASK_GAPC, KE_TASK_TYPE_TASK_GAPM as TASK_GAPM,
+    KE_TASK_TYPE_TASK_GATTC as TASK_GATTC,
 };
 
 #[inline]